@@ -1,76 +1,631 @@
-use crate::{
-  ast::{
-    Expression,
-    evaluator::value::Value,
-    operator::{Additive, Comparison, Equality, Multiplicative, Precedance, Unary}
+use {
+  crate::{
+    ast::{
+      BinaryExpression, Expression, Grouping, UnaryExpression,
+      evaluator::value::Value,
+      operator::{
+        Additive, And, Comparison, Equality, Multiplicative, Operator, Or, Precedance, Unary
+      }
+    },
+    lexer::source::Position
   },
-  lexer::{
-    source::Position,
-    token::{Keyword, TokenType}
-  }
+  getset::Getters,
+  std::fmt::Display
 };
 
 pub struct Evaluator;
 
+// Opt-in deviations from the default evaluation semantics.
+#[derive(Debug, Default, Clone)]
+pub struct EvaluatorOptions {
+  pub modulo: ModuloMode
+}
+
+// How the remainder of a division involving negative numbers is defined. The two only differ when
+// the operands have different signs : -7 % 3 is -1 when truncated, but 2 when floored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ModuloMode {
+  // The quotient is truncated towards zero, so the remainder takes the sign of the dividend. This
+  // is what f64's % does.
+  #[default]
+  Truncated,
+
+  // The quotient is floored, so the remainder takes the sign of the divisor, like Python's %.
+  Floored
+}
+
 impl<'expression> Evaluator {
-  pub fn evaluate(expression: Expression<'expression>) -> Result<Value<'expression>, ()> {
+  pub fn evaluate(expression: Expression<'expression>) -> Result<Value<'expression>, Error> {
+    Self::evaluate_with_options(expression, &EvaluatorOptions::default())
+  }
+
+  pub fn evaluate_with_options(
+    expression: Expression<'expression>,
+    options: &EvaluatorOptions
+  ) -> Result<Value<'expression>, Error> {
     Ok(match expression {
-      Expression::UnaryExpression(expression) => match expression.operator.precedance() {
+      Expression::UnaryExpression(UnaryExpression {
+        operator, operand, ..
+      }) => match operator.precedance() {
         Precedance::Unary(variant) => match variant {
-          Unary::Minus => todo!(),
-          Unary::Not => todo!()
+          Unary::Minus => match Self::evaluate_with_options(*operand, options)? {
+            Value::Number(number) => Value::Number(-number),
+
+            _ => {
+              return Err(Error {
+                position: *operator.token().position(),
+                r#type:   ErrorType::TypeMismatch {
+                  expected: "numbers"
+                }
+              });
+            }
+          },
+
+          Unary::Not | Unary::KeywordNot =>
+            Value::Boolean(!Self::evaluate_with_options(*operand, options)?.is_truthy()),
         },
 
         _ => unreachable!()
       },
 
-      Expression::BinaryExpression(expression) => match expression.operator.precedance() {
+      Expression::BinaryExpression(BinaryExpression {
+        left_operand,
+        operator,
+        right_operand,
+        ..
+      }) => match operator.precedance() {
         Precedance::Multiplicative(variant) => match variant {
-          Multiplicative::Multiply => todo!(),
-          Multiplicative::Divide => todo!()
+          Multiplicative::Multiply => {
+            let (left, right) =
+              Self::number_operands(*left_operand, *right_operand, &operator, options)?;
+            Value::Number((left * right).into())
+          }
+
+          Multiplicative::Divide => {
+            let (left, right) =
+              Self::number_operands(*left_operand, *right_operand, &operator, options)?;
+
+            // Rather than producing an infinity.
+            if right == 0.0 {
+              return Err(Error {
+                position: *operator.token().position(),
+                r#type:   ErrorType::DivisionByZero
+              });
+            }
+
+            Value::Number((left / right).into())
+          }
+
+          Multiplicative::Modulo => {
+            let (left, right) =
+              Self::number_operands(*left_operand, *right_operand, &operator, options)?;
+
+            // Rather than producing a NaN, in either mode.
+            if right == 0.0 {
+              return Err(Error {
+                position: *operator.token().position(),
+                r#type:   ErrorType::DivisionByZero
+              });
+            }
+
+            // The floored remainder is derived from the truncated one, which is exact. Rather than
+            // from the quotient, which loses precision for large operands (and is NaN for an
+            // infinite divisor).
+            let remainder = left % right;
+            let remainder = match options.modulo {
+              ModuloMode::Floored if (remainder != 0.0) && ((remainder < 0.0) != (right < 0.0)) =>
+                remainder + right,
+
+              _ => remainder
+            };
+            Value::Number(remainder.into())
+          }
+
+          // Floors the quotient.
+          Multiplicative::IntegerDivide => {
+            let (left, right) =
+              Self::number_operands(*left_operand, *right_operand, &operator, options)?;
+
+            if right == 0.0 {
+              return Err(Error {
+                position: *operator.token().position(),
+                r#type:   ErrorType::DivisionByZero
+              });
+            }
+
+            Value::Number((left / right).floor().into())
+          }
         },
 
         Precedance::Additive(variant) => match variant {
-          Additive::Plus => todo!(),
-          Additive::Minus => todo!()
+          // Adds numbers, and concatenates strings.
+          Additive::Plus => match (
+            Self::evaluate_with_options(*left_operand, options)?,
+            Self::evaluate_with_options(*right_operand, options)?
+          ) {
+            (Value::Number(left), Value::Number(right)) => Value::Number(left + right),
+
+            (Value::String(left), Value::String(right)) => Value::String(left + right),
+
+            _ => {
+              return Err(Error {
+                position: *operator.token().position(),
+                r#type:   ErrorType::TypeMismatch {
+                  expected: "two numbers or two strings"
+                }
+              });
+            }
+          },
+
+          Additive::Minus => {
+            let (left, right) =
+              Self::number_operands(*left_operand, *right_operand, &operator, options)?;
+            Value::Number((left - right).into())
+          }
         },
 
         Precedance::Comparison(variant) => match variant {
-          Comparison::GreaterThan => todo!(),
-          Comparison::GreaterThanOrEquals => todo!(),
-          Comparison::LessThan => todo!(),
-          Comparison::LessThanOrEquals => todo!()
+          Comparison::GreaterThan => {
+            let (left, right) =
+              Self::number_operands(*left_operand, *right_operand, &operator, options)?;
+            Value::Boolean(left > right)
+          }
+
+          Comparison::GreaterThanOrEquals => {
+            let (left, right) =
+              Self::number_operands(*left_operand, *right_operand, &operator, options)?;
+            Value::Boolean(left >= right)
+          }
+
+          Comparison::LessThan => {
+            let (left, right) =
+              Self::number_operands(*left_operand, *right_operand, &operator, options)?;
+            Value::Boolean(left < right)
+          }
+
+          Comparison::LessThanOrEquals => {
+            let (left, right) =
+              Self::number_operands(*left_operand, *right_operand, &operator, options)?;
+            Value::Boolean(left <= right)
+          }
         },
 
         Precedance::Equality(variant) => match variant {
-          Equality::Equals => todo!(),
-          Equality::NotEquals => todo!()
+          Equality::Equals => Value::Boolean(
+            Self::evaluate_with_options(*left_operand, options)?
+              == Self::evaluate_with_options(*right_operand, options)?
+          ),
+
+          Equality::NotEquals => Value::Boolean(
+            Self::evaluate_with_options(*left_operand, options)?
+              != Self::evaluate_with_options(*right_operand, options)?
+          )
         },
 
-        _ => unreachable!()
-      },
+        // Short circuits, evaluating to whichever operand decided the result, rather than to a
+        // boolean.
+        Precedance::And(And::And) => {
+          let left = Self::evaluate_with_options(*left_operand, options)?;
+          if !left.is_truthy() {
+            return Ok(left);
+          }
 
-      Expression::Literal(token) => match token.r#type() {
-        TokenType::Number(number) => Value::Number(*number),
+          Self::evaluate_with_options(*right_operand, options)?
+        }
 
-        TokenType::String(string) => Value::String(string),
+        Precedance::Or(Or::Or) => {
+          let left = Self::evaluate_with_options(*left_operand, options)?;
+          if left.is_truthy() {
+            return Ok(left);
+          }
 
-        TokenType::Keyword(Keyword::True) => Value::Boolean(true),
-        TokenType::Keyword(Keyword::False) => Value::Boolean(false),
+          Self::evaluate_with_options(*right_operand, options)?
+        }
 
         _ => unreachable!()
+      },
+
+      Expression::Grouping(Grouping { inner, .. }) => Self::evaluate_with_options(*inner, options)?,
+
+      Expression::Literal(token) => match token.r#type().to_literal_value() {
+        Some(value) => value,
+
+        // The only literals without a value are identifiers. There are no variables yet, so none
+        // of them is defined.
+        None => {
+          return Err(Error {
+            position: *token.position(),
+            r#type:   ErrorType::UndefinedVariable
+          });
+        }
       }
     })
   }
+
+  // Evaluates both the operands of a binary operator, which only works on numbers.
+  fn number_operands(
+    left_operand: Expression<'expression>,
+    right_operand: Expression<'expression>,
+    operator: &Operator,
+    options: &EvaluatorOptions
+  ) -> Result<(f64, f64), Error> {
+    match (
+      Self::evaluate_with_options(left_operand, options)?,
+      Self::evaluate_with_options(right_operand, options)?
+    ) {
+      (Value::Number(left), Value::Number(right)) => Ok((*left, *right)),
+
+      _ => Err(Error {
+        position: *operator.token().position(),
+        r#type:   ErrorType::TypeMismatch {
+          expected: "numbers"
+        }
+      })
+    }
+  }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Getters)]
 pub struct Error {
+  #[getset(get = "pub")]
   position: Position,
-  r#type:   ErrorType
+
+  #[getset(get = "pub")]
+  r#type: ErrorType
 }
 
+impl Error {
+  // Renders the error, along with the offending line of source and a caret under the column.
+  pub fn render(&self, source: &str) -> String {
+    self.position.render(source, &self.r#type.to_string())
+  }
+}
+
+impl Display for Error {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      formatter,
+      "runtime error at {}: {}",
+      self.position, self.r#type
+    )
+  }
+}
+
+impl std::error::Error for Error {}
+
 #[derive(Debug, strum::Display)]
-pub enum ErrorType {}
+pub enum ErrorType {
+  // What the operator expects its operands to be. Like numbers, or two numbers or two strings for
+  // plus, which also concatenates strings.
+  #[strum(to_string = "operands must be {expected}")]
+  TypeMismatch { expected: &'static str },
+
+  #[strum(to_string = "division by zero")]
+  DivisionByZero,
+
+  #[strum(to_string = "undefined variable")]
+  UndefinedVariable
+}
 
 pub mod value;
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::{ast::parser::Parser, lexer::Lexer}
+  };
+
+  fn evaluate(source: &str) -> Result<Value<'_>, Error> {
+    evaluate_with_options(source, &EvaluatorOptions::default())
+  }
+
+  fn evaluate_with_options<'source>(
+    source: &'source str,
+    options: &EvaluatorOptions
+  ) -> Result<Value<'source>, Error> {
+    let expression = Parser::new(Lexer::new(source).lex().unwrap())
+      .parse()
+      .unwrap();
+
+    Evaluator::evaluate_with_options(*expression, options)
+  }
+
+  #[test]
+  fn nil() {
+    assert_eq!(evaluate("nil").unwrap(), Value::Nil);
+    assert_eq!(evaluate("nil == nil").unwrap(), Value::Boolean(true));
+    assert_eq!(evaluate("nil != false").unwrap(), Value::Boolean(true));
+
+    // nil is falsey.
+    assert_eq!(evaluate("!nil").unwrap(), Value::Boolean(true));
+    assert_eq!(evaluate("nil or 1").unwrap(), Value::Number(1.0.into()));
+  }
+
+  #[test]
+  fn negation() {
+    assert!(matches!(evaluate("-5"), Ok(Value::Number(number)) if number == -5.0));
+    assert!(matches!(evaluate("--5"), Ok(Value::Number(number)) if number == 5.0));
+
+    let error = evaluate("-\"five\"").unwrap_err();
+    assert!(matches!(error.r#type(), ErrorType::TypeMismatch { .. }));
+    assert_eq!(error.position().column(), 1);
+  }
+
+  #[test]
+  fn not() {
+    let cases = [
+      ("!true", false),
+      ("!false", true),
+      // Only nil and false are falsey.
+      ("!0", false),
+      ("!\"\"", false),
+      ("!!1", true),
+      ("!(1 > 2)", true),
+      // not is the same as !.
+      ("not true", false),
+      ("not nil", true),
+      ("not !1", true),
+      ("not (1 > 2)", true)
+    ];
+
+    for (source, expected) in cases {
+      assert!(
+        matches!(evaluate(source), Ok(Value::Boolean(boolean)) if boolean == expected),
+        "source : {source}"
+      );
+    }
+  }
+
+  #[test]
+  fn additive() {
+    assert!(matches!(evaluate("1 + 2"), Ok(Value::Number(number)) if number == 3.0));
+    assert!(matches!(evaluate("5 - 3"), Ok(Value::Number(number)) if number == 2.0));
+    assert!(matches!(evaluate("1 + 10 % 3 - 4"), Ok(Value::Number(number)) if number == -2.0));
+  }
+
+  #[test]
+  fn string_concatenation() {
+    assert_eq!(
+      evaluate("\"foo\" + \"bar\"").unwrap(),
+      Value::String("foobar".into())
+    );
+    assert_eq!(
+      evaluate("\"\" + \"a\\n\" + \"\"").unwrap(),
+      Value::String("a\n".into())
+    );
+
+    // Strings and numbers don't mix.
+    let error = evaluate("\"one\" + 1").unwrap_err();
+    assert!(matches!(error.r#type(), ErrorType::TypeMismatch { .. }));
+    assert_eq!(error.position().column(), 7);
+    assert_eq!(
+      error.r#type().to_string(),
+      "operands must be two numbers or two strings"
+    );
+
+    let error = evaluate("1 + true").unwrap_err();
+    assert!(matches!(
+      error.r#type(),
+      ErrorType::TypeMismatch {
+        expected: "two numbers or two strings"
+      }
+    ));
+  }
+
+  #[test]
+  fn additive_type_mismatch() {
+    let error = evaluate("1 - \"one\"").unwrap_err();
+
+    assert!(matches!(error.r#type(), ErrorType::TypeMismatch { .. }));
+    assert_eq!(error.position().column(), 3);
+  }
+
+  #[test]
+  fn multiplicative() {
+    assert!(matches!(evaluate("6 * 7"), Ok(Value::Number(number)) if number == 42.0));
+    assert!(matches!(evaluate("10 / 2"), Ok(Value::Number(number)) if number == 5.0));
+  }
+
+  #[test]
+  fn integer_division() {
+    assert!(matches!(evaluate("7 div 2"), Ok(Value::Number(number)) if number == 3.0));
+    assert!(matches!(evaluate("-7 div 2"), Ok(Value::Number(number)) if number == -4.0));
+    assert!(matches!(
+      evaluate("1 div 0").unwrap_err().r#type(),
+      ErrorType::DivisionByZero
+    ));
+  }
+
+  #[test]
+  fn integer_division_type_mismatch() {
+    let error = evaluate("\"seven\" div 2").unwrap_err();
+
+    assert!(matches!(error.r#type(), ErrorType::TypeMismatch { .. }));
+    assert_eq!(error.position().column(), 9);
+  }
+
+  #[test]
+  fn division_by_zero() {
+    let error = evaluate("1 / 0").unwrap_err();
+
+    assert!(matches!(error.r#type(), ErrorType::DivisionByZero));
+    assert_eq!(error.position().column(), 3);
+  }
+
+  #[test]
+  fn comparison() {
+    let cases = [
+      ("2 > 1", true),
+      ("1 > 2", false),
+      ("2 >= 2", true),
+      ("1 >= 2", false),
+      ("1 < 2", true),
+      ("2 < 1", false),
+      ("2 <= 2", true),
+      ("3 <= 2", false),
+      ("1 + 2 < 2 * 2", true)
+    ];
+
+    for (source, expected) in cases {
+      assert!(
+        matches!(evaluate(source), Ok(Value::Boolean(boolean)) if boolean == expected),
+        "source : {source}"
+      );
+    }
+  }
+
+  #[test]
+  fn comparison_type_mismatch() {
+    let error = evaluate("\"a\" < \"b\"").unwrap_err();
+
+    assert!(matches!(error.r#type(), ErrorType::TypeMismatch { .. }));
+    assert_eq!(error.position().column(), 5);
+  }
+
+  #[test]
+  fn equality() {
+    let cases = [
+      ("1 == 1", true),
+      ("1 != 2", true),
+      ("1 != 1", false),
+      ("\"a\" == \"a\"", true),
+      ("\"a\" == \"b\"", false),
+      ("(1 < 2) == (2 < 3)", true),
+      ("true == true", true),
+      ("true != false", true),
+      // Values of different types are simply unequal.
+      ("1 == \"a\"", false),
+      ("1 != \"1\"", true)
+    ];
+
+    for (source, expected) in cases {
+      assert!(
+        matches!(evaluate(source), Ok(Value::Boolean(boolean)) if boolean == expected),
+        "source : {source}"
+      );
+    }
+  }
+
+  #[test]
+  fn logical() {
+    assert!(matches!(evaluate("1 and 2"), Ok(Value::Number(number)) if number == 2.0));
+    assert!(matches!(evaluate("false and 2"), Ok(Value::Boolean(false))));
+    assert!(matches!(evaluate("false or \"x\""), Ok(Value::String(string)) if string == "x"));
+    assert!(matches!(evaluate("0 or 1"), Ok(Value::Number(number)) if number == 0.0));
+
+    // The right operand isn't evaluated, when the left one decides the result.
+    assert!(matches!(
+      evaluate("false and 1 / 0"),
+      Ok(Value::Boolean(false))
+    ));
+    assert!(matches!(
+      evaluate("true or 1 / 0"),
+      Ok(Value::Boolean(true))
+    ));
+  }
+
+  #[test]
+  fn undefined_variable() {
+    let error = evaluate("1 + (2 * x)").unwrap_err();
+
+    assert!(matches!(error.r#type(), ErrorType::UndefinedVariable));
+    assert_eq!(error.position().to_line_col(), (1, 10));
+  }
+
+  #[test]
+  fn type_mismatch_position() {
+    // Reported at the operator, which may not be on the same line as the operands.
+    let error = evaluate("(1 < 2)\n  * 3").unwrap_err();
+
+    assert!(matches!(error.r#type(), ErrorType::TypeMismatch { .. }));
+    assert_eq!(error.position().to_line_col(), (2, 3));
+    assert_eq!(
+      error.render("(1 < 2)\n  * 3"),
+      "error: operands must be numbers\n  --> line 2, column 3\n   |   * 3\n   |   ^\n"
+    );
+  }
+
+  #[test]
+  fn display_error() {
+    let error = evaluate("1 +\n  \"one\"").unwrap_err();
+
+    assert_eq!(
+      error.to_string(),
+      "runtime error at line 1, column 3: operands must be two numbers or two strings"
+    );
+
+    let error: Box<dyn std::error::Error> = Box::new(evaluate("1 / 0").unwrap_err());
+    assert_eq!(
+      error.to_string(),
+      "runtime error at line 1, column 3: division by zero"
+    );
+  }
+
+  #[test]
+  fn modulo() {
+    assert!(matches!(evaluate("10 % 3"), Ok(Value::Number(number)) if number == 1.0));
+    assert!(matches!(evaluate("7.5 % 2"), Ok(Value::Number(number)) if number == 1.5));
+  }
+
+  #[test]
+  fn modulo_modes() {
+    let truncated = EvaluatorOptions {
+      modulo: ModuloMode::Truncated
+    };
+    assert!(matches!(
+      evaluate_with_options("-7 % 3", &truncated),
+      Ok(Value::Number(number)) if number == -1.0
+    ));
+    assert!(matches!(
+      evaluate_with_options("7 % -3", &truncated),
+      Ok(Value::Number(number)) if number == 1.0
+    ));
+
+    let floored = EvaluatorOptions {
+      modulo: ModuloMode::Floored
+    };
+    assert!(matches!(
+      evaluate_with_options("-7 % 3", &floored),
+      Ok(Value::Number(number)) if number == 2.0
+    ));
+    assert!(matches!(
+      evaluate_with_options("7 % -3", &floored),
+      Ok(Value::Number(number)) if number == -2.0
+    ));
+
+    // Exact, even when the quotient isn't representable.
+    assert!(matches!(
+      evaluate_with_options("-100000000000000000 % 3", &floored),
+      Ok(Value::Number(number)) if number == 2.0
+    ));
+    assert!(matches!(
+      evaluate_with_options("5 % (1e308 * 10)", &floored),
+      Ok(Value::Number(number)) if number == 5.0
+    ));
+
+    // Truncated is the default.
+    assert_eq!(EvaluatorOptions::default().modulo, ModuloMode::Truncated);
+  }
+
+  #[test]
+  fn modulo_by_zero() {
+    for modulo in [ModuloMode::Truncated, ModuloMode::Floored] {
+      let error = evaluate_with_options("7 % 0", &EvaluatorOptions { modulo }).unwrap_err();
+
+      assert!(
+        matches!(error.r#type(), ErrorType::DivisionByZero),
+        "mode : {modulo:?}"
+      );
+      assert_eq!(error.position().column(), 3);
+    }
+  }
+
+  #[test]
+  fn modulo_type_mismatch() {
+    let error = evaluate("\"ten\" % 3").unwrap_err();
+
+    assert!(matches!(error.r#type(), ErrorType::TypeMismatch { .. }));
+    assert_eq!(error.position().column(), 7);
+  }
+}
@@ -1,7 +1,243 @@
-use ordered_float::OrderedFloat;
+use {
+  ordered_float::OrderedFloat,
+  std::{borrow::Cow, fmt::Display}
+};
 
+// Values of different types are never equal.
+#[derive(Debug, PartialEq)]
 pub enum Value<'value> {
   Number(OrderedFloat<f64>),
-  String(&'value str),
-  Boolean(bool)
+  String(Cow<'value, str>),
+  Boolean(bool),
+  Nil
+}
+
+impl Value<'_> {
+  // Lox follows Ruby's simple rule : nil and false are falsey, and everything else is truthy.
+  pub fn is_truthy(&self) -> bool {
+    !matches!(self, Value::Nil | Value::Boolean(false))
+  }
+
+  pub fn to_string_with(&self, number_format: &NumberFormat) -> String {
+    match self {
+      Value::Number(number) => number_format.format(**number),
+
+      _ => self.to_string()
+    }
+  }
+
+  // Canonical rendering with explicit type tags, for snapshot testing evaluation results. Unlike
+  // Display, it tells apart the number 1 from the string "1", and shows the escapes in a string.
+  pub fn dump(&self) -> String {
+    match self {
+      Value::Number(number) => format!("number({number:?})"),
+      Value::String(string) => format!("string({string:?})"),
+      Value::Boolean(boolean) => format!("boolean({boolean})"),
+      Value::Nil => "nil".to_string()
+    }
+  }
+}
+
+impl Display for Value<'_> {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Value::Number(number) => write!(formatter, "{number}"),
+      Value::String(string) => write!(formatter, "{string}"),
+      Value::Boolean(boolean) => write!(formatter, "{boolean}"),
+      Value::Nil => write!(formatter, "nil")
+    }
+  }
+}
+
+// How numbers get displayed. By default, they're rendered in the shortest form which round trips
+// back to the same number (like 100000000000000000000), but very large or very small magnitudes
+// can be rendered in scientific notation (like 1e20) instead, the way calculators do.
+#[derive(Debug, Default, Clone)]
+pub struct NumberFormat {
+  // Magnitudes at or above this are rendered in scientific notation.
+  pub scientific_above: Option<f64>,
+
+  // Non-zero magnitudes below this are rendered in scientific notation.
+  pub scientific_below: Option<f64>
+}
+
+impl NumberFormat {
+  pub fn format(&self, number: f64) -> String {
+    let magnitude = number.abs();
+
+    let is_scientific = self
+      .scientific_above
+      .is_some_and(|threshold| magnitude >= threshold)
+      || self
+        .scientific_below
+        .is_some_and(|threshold| (magnitude != 0.0) && (magnitude < threshold));
+
+    if is_scientific {
+      return format!("{number:e}");
+    }
+
+    format!("{number}")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn display() {
+    let values = [
+      Value::Number(5.0.into()),
+      Value::Number(2.5.into()),
+      Value::Number((-0.125).into()),
+      Value::String("say \"hi\"".into()),
+      Value::Boolean(true),
+      Value::Boolean(false),
+      Value::Nil
+    ];
+
+    assert_eq!(
+      values.iter().map(Value::to_string).collect::<Vec<_>>(),
+      ["5", "2.5", "-0.125", "say \"hi\"", "true", "false", "nil"]
+    );
+  }
+
+  #[test]
+  fn shortest_round_trip_by_default() {
+    assert_eq!(
+      Value::Number(1e20.into()).to_string(),
+      "100000000000000000000"
+    );
+    assert_eq!(
+      Value::Number(1e20.into()).to_string_with(&NumberFormat::default()),
+      "100000000000000000000"
+    );
+  }
+
+  #[test]
+  fn round_trips() {
+    let numbers = [
+      0.1 + 0.2,
+      1234567.5,
+      1e-7,
+      1e300,
+      -123.456,
+      f64::MIN_POSITIVE,
+      f64::MAX
+    ];
+
+    for number in numbers {
+      let rendered = Value::Number(number.into()).to_string();
+      assert_eq!(rendered.parse::<f64>(), Ok(number), "rendered : {rendered}");
+    }
+
+    // Not rounded to fewer digits. And, regardless of the platform's locale, there's no digit
+    // grouping and the decimal separator is always a dot.
+    assert_eq!(
+      Value::Number((0.1 + 0.2).into()).to_string(),
+      "0.30000000000000004"
+    );
+    assert_eq!(Value::Number(1234567.5.into()).to_string(), "1234567.5");
+  }
+
+  // Negative zero (like the result of -0 or -1 * 0) is rendered with its sign, in either format.
+  #[test]
+  fn negative_zero() {
+    assert_eq!(Value::Number((-0.0).into()).to_string(), "-0");
+
+    let number_format = NumberFormat {
+      scientific_above: Some(1e15),
+      scientific_below: Some(1e-4)
+    };
+    assert_eq!(
+      Value::Number((-0.0).into()).to_string_with(&number_format),
+      "-0"
+    );
+  }
+
+  #[test]
+  fn scientific_notation() {
+    let number_format = NumberFormat {
+      scientific_above: Some(1e15),
+      scientific_below: Some(1e-4)
+    };
+
+    // Beyond the thresholds.
+    assert_eq!(
+      Value::Number(1e20.into()).to_string_with(&number_format),
+      "1e20"
+    );
+    assert_eq!(
+      Value::Number((-2.5e16).into()).to_string_with(&number_format),
+      "-2.5e16"
+    );
+    assert_eq!(
+      Value::Number(0.00001.into()).to_string_with(&number_format),
+      "1e-5"
+    );
+
+    // Within the thresholds.
+    assert_eq!(
+      Value::Number(12345.0.into()).to_string_with(&number_format),
+      "12345"
+    );
+    assert_eq!(
+      Value::Number(0.5.into()).to_string_with(&number_format),
+      "0.5"
+    );
+    assert_eq!(
+      Value::Number(0.0.into()).to_string_with(&number_format),
+      "0"
+    );
+
+    // Only numbers are affected.
+    assert_eq!(Value::Boolean(true).to_string_with(&number_format), "true");
+  }
+
+  #[test]
+  fn truthiness() {
+    assert!(!Value::Nil.is_truthy());
+    assert!(!Value::Boolean(false).is_truthy());
+
+    assert!(Value::Boolean(true).is_truthy());
+    assert!(Value::Number(0.0.into()).is_truthy());
+    assert!(Value::String("".into()).is_truthy());
+  }
+
+  #[test]
+  fn equality() {
+    assert_eq!(Value::Boolean(true), Value::Boolean(true));
+    assert_eq!(Value::Nil, Value::Nil);
+    assert_eq!(
+      Value::String("a".into()),
+      Value::String(String::from("a").into())
+    );
+
+    assert_ne!(Value::Number(1.0.into()), Value::String("1".into()));
+    assert_ne!(Value::Boolean(false), Value::Nil);
+  }
+
+  #[test]
+  fn dump() {
+    let values = [
+      Value::Number(1.0.into()),
+      Value::Number((-2.5).into()),
+      Value::String("1".into()),
+      Value::String("say \"hi\"\n".into()),
+      Value::Boolean(false),
+      Value::Nil
+    ];
+
+    assert_eq!(
+      values.iter().map(Value::dump).collect::<Vec<_>>(),
+      [
+        "number(1.0)",
+        "number(-2.5)",
+        "string(\"1\")",
+        "string(\"say \\\"hi\\\"\\n\")",
+        "boolean(false)",
+        "nil"
+      ]
+    );
+  }
 }
@@ -1,14 +1,29 @@
 /*
   Operator precedance is the same as that in C :
 
-    (1) -, !
-    (2) /, *
+    (1) -, !, not
+    (2) /, *, %, div
     (3) +, -
     (4) >, >=, <, <=
     (5) ==, !=
+    (6) and
+    (7) or
 */
 
-expression -> equality;
+program -> statement* EOF;
+
+statement -> expression-statement
+           | print-statement;
+
+expression-statement -> expression ";";
+
+print-statement -> "print" expression ";";
+
+expression -> or;
+
+or -> and ("or" and)*;
+
+and -> equality ("and" equality)*;
 
 equality -> comparison (("==" | "!=") comparison)*;
 
@@ -16,9 +31,9 @@ comparison -> additive-expression ((">" | ">=" | "<" | "<=") additive-expression
 
 additive-expression -> multiplicative-expression (("+" | "-") multiplicative-expression)*;
 
-multiplicative-expression -> unary-expression (("*" | "/") unary-expression)*;
+multiplicative-expression -> unary-expression (("*" | "/" | "%" | "div") unary-expression)*;
 
-unary-expression -> ("-" | "!") unary-expression
+unary-expression -> ("-" | "!" | "not") unary-expression
                   | paranthesized;
 
 paranthesized -> "(" expression ")"
@@ -44,29 +44,117 @@
   The formal grammar for Lox interpreter is defined at ./grammar.g.
 */
 
-use crate::{ast::operator::Operator, lexer::token::Token};
+use {
+  crate::{
+    ast::operator::Operator,
+    lexer::{source::Span, token::Token}
+  },
+  getset::Getters
+};
 
 #[derive(Debug)]
 pub enum Expression<'expression> {
   Literal(Token<'expression>),
   UnaryExpression(UnaryExpression<'expression>),
-  BinaryExpression(BinaryExpression<'expression>)
+  BinaryExpression(BinaryExpression<'expression>),
+  Grouping(Grouping<'expression>)
 }
 
-#[derive(Debug)]
+impl<'expression> Expression<'expression> {
+  // The span of an expression covers all the tokens it was parsed from, including the paranthesis
+  // around a grouped expression.
+  pub fn span(&self) -> Span {
+    match self {
+      Expression::Literal(token) => *token.span(),
+      Expression::UnaryExpression(unary_expression) => unary_expression.span,
+      Expression::BinaryExpression(binary_expression) => binary_expression.span,
+      Expression::Grouping(grouping) => grouping.span
+    }
+  }
+
+  // The source text the expression was parsed from.
+  pub fn source_text<'source>(&self, source: &'source str) -> &'source str {
+    self.span().slice(source)
+  }
+
+  // Counts the nodes of each kind in the expression tree.
+  pub fn node_counts(&self) -> NodeCounts {
+    let mut node_counts = NodeCounts::default();
+    self.count_nodes(&mut node_counts);
+
+    node_counts
+  }
+
+  fn count_nodes(&self, node_counts: &mut NodeCounts) {
+    match self {
+      Expression::Literal(_) => node_counts.literals += 1,
+
+      Expression::UnaryExpression(unary_expression) => {
+        node_counts.unary_expressions += 1;
+        unary_expression.operand.count_nodes(node_counts);
+      }
+
+      Expression::BinaryExpression(binary_expression) => {
+        node_counts.binary_expressions += 1;
+        binary_expression.left_operand.count_nodes(node_counts);
+        binary_expression.right_operand.count_nodes(node_counts);
+      }
+
+      // The paranthesis only affect the shape of the tree, so they aren't counted as a node.
+      Expression::Grouping(grouping) => grouping.inner.count_nodes(node_counts)
+    }
+  }
+}
+
+// Number of nodes of each kind in an AST. Useful as a complexity metric.
+#[derive(Debug, Default, PartialEq, Eq, Getters)]
+pub struct NodeCounts {
+  #[getset(get = "pub")]
+  literals: usize,
+
+  #[getset(get = "pub")]
+  unary_expressions: usize,
+
+  #[getset(get = "pub")]
+  binary_expressions: usize
+}
+
+#[derive(Debug, Getters)]
 pub struct UnaryExpression<'unary_expression> {
+  #[getset(get = "pub")]
   operator: Operator<'unary_expression>,
-  operand:  Box<Expression<'unary_expression>>
+
+  #[getset(get = "pub")]
+  operand: Box<Expression<'unary_expression>>,
+
+  span: Span
 }
 
-#[derive(Debug)]
+#[derive(Debug, Getters)]
 pub struct BinaryExpression<'binary_expression> {
-  left_operand:  Box<Expression<'binary_expression>>,
-  operator:      Operator<'binary_expression>,
-  right_operand: Box<Expression<'binary_expression>>
+  #[getset(get = "pub")]
+  left_operand: Box<Expression<'binary_expression>>,
+
+  #[getset(get = "pub")]
+  operator: Operator<'binary_expression>,
+
+  #[getset(get = "pub")]
+  right_operand: Box<Expression<'binary_expression>>,
+
+  span: Span
+}
+
+// An expression wrapped in paranthesis.
+#[derive(Debug, Getters)]
+pub struct Grouping<'grouping> {
+  #[getset(get = "pub")]
+  inner: Box<Expression<'grouping>>,
+
+  span: Span
 }
 
 pub mod evaluator;
 pub mod operator;
 pub mod parser;
 pub mod printer;
+pub mod statement;
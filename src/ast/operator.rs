@@ -1,7 +1,7 @@
 use {
   crate::{
-    ast::parser::Parser,
-    lexer::token::{Token, TokenType}
+    ast::parser::{Error, Parser},
+    lexer::token::{Keyword, Token, TokenType}
   },
   getset::Getters,
   paste::paste
@@ -12,9 +12,18 @@ pub struct Operator<'operator> {
   #[getset(get = "pub")]
   precedance: Precedance,
 
+  #[getset(get = "pub")]
   token: Token<'operator>
 }
 
+impl Operator<'_> {
+  // The operator exactly as it was written in the source, which the precedance's display might
+  // differ from, if the operator is an alias.
+  pub fn lexeme<'source>(&self, source: &'source str) -> &'source str {
+    self.token.span().slice(source)
+  }
+}
+
 #[derive(Debug, strum_macros::Display)]
 pub enum Precedance {
   #[strum(to_string = "{0}")]
@@ -30,11 +39,79 @@ pub enum Precedance {
   Comparison(Comparison),
 
   #[strum(to_string = "{0}")]
-  Equality(Equality)
+  Equality(Equality),
+
+  #[strum(to_string = "{0}")]
+  And(And),
+
+  #[strum(to_string = "{0}")]
+  Or(Or)
+}
+
+impl Precedance {
+  // The operator precedance table, from the tightest binding operators to the loosest.
+  pub fn table() -> Vec<OperatorInfo> {
+    [
+      Unary::operators(),
+      Multiplicative::operators(),
+      Additive::operators(),
+      Comparison::operators(),
+      Equality::operators(),
+      And::operators(),
+      Or::operators()
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+  }
+}
+
+#[derive(Debug, Getters)]
+pub struct OperatorInfo {
+  #[getset(get = "pub")]
+  symbol: String,
+
+  // Name of the precedance level, like Multiplicative.
+  #[getset(get = "pub")]
+  precedance: &'static str,
+
+  // 1 binds the tightest.
+  #[getset(get = "pub")]
+  level: usize,
+
+  #[getset(get = "pub")]
+  associativity: Associativity
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Associativity {
+  Left,
+  Right
+}
+
+// The token type an operator variant corresponds to. Unless specified otherwise (like
+// Modulo = Percent, or IntegerDivide = Keyword(Div)), it's the token type with the same name.
+macro_rules! token_type {
+  ($variant:ident) => {
+    TokenType::$variant
+  };
+
+  ($variant:ident = $token_type:ident) => {
+    TokenType::$token_type
+  };
+
+  ($variant:ident = Keyword($keyword:ident)) => {
+    TokenType::Keyword(Keyword::$keyword)
+  };
 }
 
 macro_rules! create_precedance {
-  ($name:ident { $($variant:ident),+ }) => {
+  (
+    $name:ident { $($variant:ident $(= $token_type:ident $(($keyword:ident))?)?),+ },
+    level = $level:literal,
+    associativity = $associativity:ident
+  ) => {
     paste!{
 
       #[derive(Debug, strum_macros::Display)]
@@ -46,12 +123,25 @@ macro_rules! create_precedance {
         pub fn try_from(token_type: &TokenType<'a>) -> Option<Self> {
           Some(match token_type {
             $(
-              TokenType::$variant => Self::$variant,
+              token_type!($variant $(= $token_type $(($keyword))?)?) => Self::$variant,
             )+
 
             _ => return None
           })
         }
+
+        pub fn operators() -> Vec<OperatorInfo> {
+          vec![
+            $(
+              OperatorInfo {
+                symbol: token_type!($variant $(= $token_type $(($keyword))?)?).to_string(),
+                precedance: stringify!($name),
+                level: $level,
+                associativity: Associativity::$associativity
+              },
+            )+
+          ]
+        }
       }
 
       // Corresponding to each precedance level, we define a utility method for Parser.
@@ -59,15 +149,20 @@ macro_rules! create_precedance {
       // method will be defined. As you can infer from the name, it'll progress the tokens
       // iterator if the next token is of type comparison operator.
       impl<'parser> Parser<'parser> {
-        pub(crate) fn [<next_if_ $name:lower _operator>](&mut self) -> Option<Operator<'parser>> {
-          let token = self.tokens.peek()?;
+        pub(crate) fn [<next_if_ $name:lower _operator>](
+          &mut self
+        ) -> Result<Option<Operator<'parser>>, Error> {
+          let mut variant = None;
 
-          let variant = $name::try_from(token.r#type())?;
+          let token = self.next_if(|token| {
+            variant = $name::try_from(token.r#type());
+            variant.is_some()
+          })?;
 
-          Some(Operator {
+          Ok(token.zip(variant).map(|(token, variant)| Operator {
             precedance: Precedance::$name(variant),
-            token: self.tokens.next()?,
-          })
+            token
+          }))
         }
       }
 
@@ -75,17 +170,111 @@ macro_rules! create_precedance {
   };
 }
 
-create_precedance!(Unary { Minus, Not });
+// not is a spelled out alias for !.
+create_precedance!(
+  Unary {
+    Minus,
+    Not,
+    KeywordNot = Keyword(Not)
+  },
+  level = 1,
+  associativity = Right
+);
+
+create_precedance!(
+  Multiplicative {
+    Multiply,
+    Divide,
+    Modulo = Percent,
+    IntegerDivide = Keyword(Div)
+  },
+  level = 2,
+  associativity = Left
+);
+
+create_precedance!(Additive { Plus, Minus }, level = 3, associativity = Left);
+
+create_precedance!(
+  Comparison {
+    GreaterThan,
+    GreaterThanOrEquals,
+    LessThan,
+    LessThanOrEquals
+  },
+  level = 4,
+  associativity = Left
+);
 
-create_precedance!(Multiplicative { Multiply, Divide });
+create_precedance!(
+  Equality { Equals, NotEquals },
+  level = 5,
+  associativity = Left
+);
 
-create_precedance!(Additive { Plus, Minus });
+// The logical operators are keywords. And binds tighter than or, like && and || in C.
+create_precedance!(And { And = Keyword(And) }, level = 6, associativity = Left);
 
-create_precedance!(Comparison {
-  GreaterThan,
-  GreaterThanOrEquals,
-  LessThan,
-  LessThanOrEquals
-});
+create_precedance!(Or { Or = Keyword(Or) }, level = 7, associativity = Left);
 
-create_precedance!(Equality { Equals, NotEquals });
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::{ast::Expression, lexer::Lexer}
+  };
+
+  #[test]
+  fn lexeme() {
+    let source = "a  >=  b";
+    let expression = Parser::new(Lexer::new(source).lex().unwrap())
+      .parse()
+      .unwrap();
+
+    let Expression::BinaryExpression(binary_expression) = &*expression
+    else {
+      panic!("expected a binary expression");
+    };
+
+    assert_eq!(binary_expression.operator().lexeme(source), ">=");
+    assert_eq!(expression.source_text(source), "a  >=  b");
+  }
+
+  #[test]
+  fn precedance_table() {
+    let table = Precedance::table();
+
+    let find = |symbol: &str, precedance: &str| {
+      table
+        .iter()
+        .find(|operator| (operator.symbol() == symbol) && (*operator.precedance() == precedance))
+        .unwrap()
+    };
+
+    let multiply = find("*", "Multiplicative");
+    assert_eq!(*multiply.level(), 2);
+    assert_eq!(*multiply.associativity(), Associativity::Left);
+    assert_eq!(*find("%", "Multiplicative").level(), 2);
+    assert_eq!(
+      Precedance::Multiplicative(Multiplicative::Modulo).to_string(),
+      "Modulo"
+    );
+    assert_eq!(*find("div", "Multiplicative").level(), 2);
+
+    let equals = find("==", "Equality");
+    assert_eq!(*equals.level(), 5);
+    assert_eq!(*equals.associativity(), Associativity::Left);
+
+    assert_eq!(*find("and", "And").level(), 6);
+    assert_eq!(*find("or", "Or").level(), 7);
+    assert_eq!(*find("not", "Unary").level(), 1);
+
+    // - is both a unary and an additive operator.
+    let negate = find("-", "Unary");
+    assert_eq!(*negate.level(), 1);
+    assert_eq!(*negate.associativity(), Associativity::Right);
+    assert_eq!(*find("-", "Additive").level(), 3);
+
+    // The table is ordered from the tightest binding operators to the loosest.
+    assert!(table.is_sorted_by_key(|operator| *operator.level()));
+  }
+}
@@ -24,54 +24,164 @@
 
 use {
   crate::{
-    ast::{BinaryExpression, Expression, UnaryExpression},
+    ast::{BinaryExpression, Expression, Grouping, UnaryExpression, statement::Statement},
     lexer::{
-      source::Position,
-      token::{Token, TokenType}
+      self, Lexer,
+      interner::Interner,
+      source::{Position, Span},
+      token::{Keyword, Token, TokenType}
     }
   },
-  std::{iter::Peekable, vec::IntoIter}
+  getset::Getters,
+  std::{fmt::Display, iter::Peekable}
 };
 
+type Tokens<'tokens> = Box<dyn Iterator<Item = Result<Token<'tokens>, lexer::Error>> + 'tokens>;
+
 pub struct Parser<'parser> {
-  pub(crate) tokens: Peekable<IntoIter<Token<'parser>>>,
-  position:          Position
+  tokens: Peekable<Tokens<'parser>>,
+
+  // Resolves interned identifiers, when the tokens were lexed with an interner.
+  interner: Option<&'parser Interner>
 }
 
 impl<'parser> Parser<'parser> {
-  pub fn new(tokens: Vec<Token<'parser>>) -> Option<Self> {
-    if tokens.is_empty() {
-      return None;
+  // The tokens are expected to end with an EOF token, like the ones produced by the lexer.
+  pub fn new(tokens: Vec<Token<'parser>>) -> Self {
+    Self {
+      tokens:   (Box::new(tokens.into_iter().map(Ok)) as Tokens).peekable(),
+      interner: None
     }
+  }
 
-    let position = tokens[0].position().clone();
+  // Pulls tokens from the lexer only as they're needed, so that lexing and parsing are
+  // interleaved. Parsing fails fast at the first lexer error, without lexing the rest of the
+  // source.
+  pub fn from_lexer(lexer: Lexer<'parser>) -> Self {
+    Self {
+      tokens:   (Box::new(lexer) as Tokens).peekable(),
+      interner: None
+    }
+  }
 
-    Some(Self {
-      tokens: tokens.into_iter().peekable(),
-      position
-    })
+  // The interner the tokens were lexed with, for looking up the names of interned identifiers.
+  pub fn with_interner(self, interner: &'parser Interner) -> Self {
+    Self {
+      interner: Some(interner),
+      ..self
+    }
   }
 
+  // Parses a single expression, which must make up the whole of the input.
   pub fn parse(&mut self) -> Result<Box<Expression<'parser>>, Error> {
-    self.parse_expression()
+    let expression = self.parse_expression()?;
+
+    // Ensure that nothing is left over after the expression.
+    if self
+      .peek()?
+      .is_some_and(|token| *(token.r#type()) != TokenType::Eof)
+    {
+      return Err(self.error_at_next_token(ErrorType::UnexpectedToken)?);
+    }
+
+    Ok(expression)
+  }
+
+  // Parses the statements, till the EOF token.
+  pub fn parse_program(&mut self) -> Result<Vec<Statement<'parser>>, Error> {
+    let mut statements = Vec::new();
+
+    while self
+      .next_if(|token| *(token.r#type()) == TokenType::Eof)?
+      .is_none()
+    {
+      statements.push(self.parse_statement()?);
+    }
+
+    Ok(statements)
+  }
+
+  fn parse_statement(&mut self) -> Result<Statement<'parser>, Error> {
+    let is_print_statement = self
+      .next_if(|token| *(token.r#type()) == TokenType::Keyword(Keyword::Print))?
+      .is_some();
+
+    let expression = self.parse_expression()?;
+
+    // Ensure that the statement is terminated by a semicolon.
+    if self
+      .next_if(|token| *(token.r#type()) == TokenType::Semicolon)?
+      .is_none()
+    {
+      // A statement starting with a near-miss for a keyword (like fnu add()) is most likely a typo,
+      // rather than a missing semicolon.
+      if let Expression::Literal(token) = &*expression
+        && let Some(identifier) = self.identifier_name(token.r#type())
+        && let Some(keyword) = Keyword::suggestion_for(identifier)
+      {
+        return Err(Error {
+          position: *token.position(),
+          r#type:   ErrorType::MisspelledKeyword(keyword),
+          span:     *token.span()
+        });
+      }
+
+      return Err(self.error_at_next_token(ErrorType::ExpectedSemicolon)?);
+    }
+
+    if is_print_statement {
+      return Ok(Statement::Print(expression));
+    }
+
+    Ok(Statement::Expression(expression))
   }
 
   fn parse_expression(&mut self) -> Result<Box<Expression<'parser>>, Error> {
-    self.parse_equality()
+    self.parse_or()
+  }
+
+  fn parse_or(&mut self) -> Result<Box<Expression<'parser>>, Error> {
+    let mut left_operand = self.parse_and()?;
+
+    while let Some(operator) = self.next_if_or_operator()? {
+      let right_operand = self.parse_and()?;
+
+      left_operand = Box::new(Expression::BinaryExpression(BinaryExpression {
+        span: left_operand.span().merge(&right_operand.span()),
+        left_operand,
+        operator,
+        right_operand
+      }))
+    }
+
+    Ok(left_operand)
+  }
+
+  fn parse_and(&mut self) -> Result<Box<Expression<'parser>>, Error> {
+    let mut left_operand = self.parse_equality()?;
+
+    while let Some(operator) = self.next_if_and_operator()? {
+      let right_operand = self.parse_equality()?;
+
+      left_operand = Box::new(Expression::BinaryExpression(BinaryExpression {
+        span: left_operand.span().merge(&right_operand.span()),
+        left_operand,
+        operator,
+        right_operand
+      }))
+    }
+
+    Ok(left_operand)
   }
 
   fn parse_equality(&mut self) -> Result<Box<Expression<'parser>>, Error> {
     let mut left_operand = self.parse_comparison()?;
 
-    loop {
-      let operator = match self.next_if_equality_operator() {
-        Some(operator) => operator,
-        None => break
-      };
-
+    while let Some(operator) = self.next_if_equality_operator()? {
       let right_operand = self.parse_comparison()?;
 
       left_operand = Box::new(Expression::BinaryExpression(BinaryExpression {
+        span: left_operand.span().merge(&right_operand.span()),
         left_operand,
         operator,
         right_operand
@@ -84,10 +194,11 @@ impl<'parser> Parser<'parser> {
   fn parse_comparison(&mut self) -> Result<Box<Expression<'parser>>, Error> {
     let mut left_operand = self.parse_additive_expression()?;
 
-    while let Some(operator) = self.next_if_comparison_operator() {
+    while let Some(operator) = self.next_if_comparison_operator()? {
       let right_operand = self.parse_additive_expression()?;
 
       left_operand = Box::new(Expression::BinaryExpression(BinaryExpression {
+        span: left_operand.span().merge(&right_operand.span()),
         left_operand,
         operator,
         right_operand
@@ -100,10 +211,11 @@ impl<'parser> Parser<'parser> {
   fn parse_additive_expression(&mut self) -> Result<Box<Expression<'parser>>, Error> {
     let mut left_operand = self.parse_multiplicative_expression()?;
 
-    while let Some(operator) = self.next_if_additive_operator() {
+    while let Some(operator) = self.next_if_additive_operator()? {
       let right_operand = self.parse_multiplicative_expression()?;
 
       left_operand = Box::new(Expression::BinaryExpression(BinaryExpression {
+        span: left_operand.span().merge(&right_operand.span()),
         left_operand,
         operator,
         right_operand
@@ -116,10 +228,11 @@ impl<'parser> Parser<'parser> {
   fn parse_multiplicative_expression(&mut self) -> Result<Box<Expression<'parser>>, Error> {
     let mut left_operand = self.parse_unary_expression()?;
 
-    while let Some(operator) = self.next_if_multiplicative_operator() {
+    while let Some(operator) = self.next_if_multiplicative_operator()? {
       let right_operand = self.parse_unary_expression()?;
 
       left_operand = Box::new(Expression::BinaryExpression(BinaryExpression {
+        span: left_operand.span().merge(&right_operand.span()),
         left_operand,
         operator,
         right_operand
@@ -130,11 +243,12 @@ impl<'parser> Parser<'parser> {
   }
 
   fn parse_unary_expression(&mut self) -> Result<Box<Expression<'parser>>, Error> {
-    match self.next_if_unary_operator() {
+    match self.next_if_unary_operator()? {
       Some(operator) => {
         let operand = self.parse_unary_expression()?;
 
         Ok(Box::new(Expression::UnaryExpression(UnaryExpression {
+          span: operator.token().span().merge(&operand.span()),
           operator,
           operand
         })))
@@ -145,26 +259,21 @@ impl<'parser> Parser<'parser> {
   }
 
   fn parse_paranthesized(&mut self) -> Result<Box<Expression<'parser>>, Error> {
-    match self
-      .tokens
-      .next_if(|token| *(token.r#type()) == TokenType::OpenParanthesis)
-    {
+    match self.next_if(|token| *(token.r#type()) == TokenType::OpenParanthesis)? {
       Some(open_paranthesis) => {
         let inner = self.parse_expression()?;
 
         // Ensure that the closing paranthesis is there.
-        if self
-          .tokens
-          .next_if(|token| *(token.r#type()) == TokenType::CloseParanthesis)
-          .is_none()
-        {
-          return Err(Error {
-            position: open_paranthesis.position().clone(),
-            r#type:   ErrorType::ExpectedCloseParanthesis
-          });
-        }
-
-        Ok(inner)
+        let Some(close_paranthesis) =
+          self.next_if(|token| *(token.r#type()) == TokenType::CloseParanthesis)?
+        else {
+          return Err(self.error_at_next_token(ErrorType::ExpectedCloseParanthesis)?);
+        };
+
+        Ok(Box::new(Expression::Grouping(Grouping {
+          inner,
+          span: open_paranthesis.span().merge(close_paranthesis.span())
+        })))
       }
 
       _ => self.parse_literal()
@@ -172,24 +281,103 @@ impl<'parser> Parser<'parser> {
   }
 
   fn parse_literal(&mut self) -> Result<Box<Expression<'parser>>, Error> {
-    match self.tokens.next_if(|token| token.is_literal()) {
-      None => Err(Error {
-        position: self.position,
-        r#type:   ErrorType::ExpectedLiteral
-      }),
+    match self.next_if(|token| token.is_literal())? {
+      None => Err(self.error_at_next_token(ErrorType::ExpectedLiteral)?),
 
       Some(token) => Ok(Box::new(Expression::Literal(token)))
     }
   }
+
+  // Name of the identifier, if the token is one. Interned identifiers can only be looked up when
+  // the parser has the interner.
+  fn identifier_name<'token>(&'token self, r#type: &'token TokenType) -> Option<&'token str> {
+    match r#type {
+      TokenType::Identifier(identifier) => Some(identifier),
+      TokenType::InternedIdentifier(symbol) => Some(self.interner?.resolve(*symbol)),
+      _ => None
+    }
+  }
+
+  // Peeks at the next token. A lexer error encountered along the way is reported as is.
+  fn peek(&mut self) -> Result<Option<&Token<'parser>>, Error> {
+    if let Some(Err(error)) = self.tokens.next_if(Result::is_err) {
+      return Err(Error {
+        position: *error.position(),
+        r#type:   ErrorType::LexError(error.r#type().clone()),
+        span:     *error.span()
+      });
+    }
+
+    Ok(self.tokens.peek().and_then(|result| result.as_ref().ok()))
+  }
+
+  // Consumes the next token, only if it satisfies the given predicate.
+  pub(crate) fn next_if(
+    &mut self,
+    predicate: impl FnOnce(&Token<'parser>) -> bool
+  ) -> Result<Option<Token<'parser>>, Error> {
+    if !self.peek()?.is_some_and(predicate) {
+      return Ok(None);
+    }
+
+    Ok(self.tokens.next().and_then(Result::ok))
+  }
+
+  // An error at the next token, which is the EOF token once the input has been used up.
+  fn error_at_next_token(&mut self, r#type: ErrorType) -> Result<Error, Error> {
+    let (position, span) = self
+      .peek()?
+      .map(|token| (*token.position(), *token.span()))
+      .unwrap_or_default();
+
+    Ok(Error {
+      position,
+      r#type,
+      span
+    })
+  }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Getters)]
 pub struct Error {
+  #[getset(get = "pub")]
   position: Position,
-  r#type:   ErrorType
+
+  #[getset(get = "pub")]
+  r#type: ErrorType,
+
+  // The offending token, which is empty only when it's the EOF token.
+  #[getset(get = "pub")]
+  span: Span
+}
+
+impl Error {
+  // Renders the error, along with the offending line of source and a caret under the column.
+  pub fn render(&self, source: &str) -> String {
+    self.position.render(source, &self.r#type.to_string())
+  }
+
+  // Like render, but reports the position as name:line:column, when the source has a name.
+  pub fn render_named(&self, source: &str, name: Option<&str>) -> String {
+    self
+      .position
+      .render_named(source, name, &self.r#type.to_string())
+  }
 }
 
-#[derive(Debug, strum::Display)]
+impl Display for Error {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      formatter,
+      "parse error at {}: {}",
+      self.position, self.r#type
+    )
+  }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, strum::Display, strum::EnumIter)]
 pub enum ErrorType {
   #[strum(to_string = "invalid unary operator")]
   InvalidUnaryOperator,
@@ -201,14 +389,51 @@ pub enum ErrorType {
   ExpectedCloseParanthesis,
 
   #[strum(to_string = "expected a literal")]
-  ExpectedLiteral
+  ExpectedLiteral,
+
+  #[strum(to_string = "expected a semicolon")]
+  ExpectedSemicolon,
+
+  #[strum(to_string = "unexpected token")]
+  UnexpectedToken,
+
+  #[strum(to_string = "unknown; did you mean '{0}'?")]
+  MisspelledKeyword(Keyword),
+
+  // Encountered while lexing lazily, in the middle of parsing.
+  #[strum(to_string = "{0}")]
+  LexError(lexer::ErrorType)
+}
+
+impl ErrorType {
+  // Stable code identifying the error, for tooling. Codes must never be reused or reassigned.
+  pub fn code(&self) -> &'static str {
+    match self {
+      ErrorType::InvalidUnaryOperator => "P0001",
+      ErrorType::ExpectedCloseParanthesis => "P0002",
+      ErrorType::InvalidBinaryOperator => "P0003",
+      ErrorType::ExpectedLiteral => "P0004",
+      ErrorType::ExpectedSemicolon => "P0005",
+      ErrorType::UnexpectedToken => "P0006",
+      ErrorType::MisspelledKeyword(_) => "P0007",
+
+      // Keeps the code of the underlying lexer error.
+      ErrorType::LexError(r#type) => r#type.code()
+    }
+  }
 }
 
 #[cfg(test)]
 mod test {
   use {
     super::*,
-    crate::{ast::printer::Printer, lexer::Lexer}
+    crate::{
+      ast::{
+        operator::{Multiplicative, Precedance},
+        printer::Printer
+      },
+      lexer::Lexer
+    }
   };
 
   #[test]
@@ -218,10 +443,271 @@ mod test {
     let mut lexer = Lexer::new(source);
     let tokens = lexer.lex().unwrap();
 
-    let mut parser = Parser::new(tokens).unwrap();
+    let mut parser = Parser::new(tokens);
     let expression = parser.parse().unwrap();
 
     Printer::print(&expression);
+
+    assert_eq!(expression.span().slice(source), source);
+  }
+
+  #[test]
+  fn spans_include_paranthesis() {
+    let source = "(a + b) * c";
+
+    let tokens = Lexer::new(source).lex().unwrap();
+    let expression = Parser::new(tokens).parse().unwrap();
+    assert_eq!(expression.source_text(source), "(a + b) * c");
+
+    let Expression::BinaryExpression(binary_expression) = *expression
+    else {
+      panic!("expected a binary expression");
+    };
+    assert_eq!(
+      binary_expression.left_operand().source_text(source),
+      "(a + b)"
+    );
+
+    let source = "-((1))";
+    let tokens = Lexer::new(source).lex().unwrap();
+    let expression = Parser::new(tokens).parse().unwrap();
+    assert_eq!(expression.source_text(source), source);
+  }
+
+  // Renders the expression as an S-expression, making the tree structure explicit.
+  fn to_s_expression(expression: &Expression) -> String {
+    match expression {
+      Expression::Literal(token) => token.r#type().to_string(),
+
+      Expression::UnaryExpression(unary_expression) => format!(
+        "({} {})",
+        unary_expression.operator().token().r#type(),
+        to_s_expression(unary_expression.operand())
+      ),
+
+      Expression::BinaryExpression(binary_expression) => format!(
+        "({} {} {})",
+        binary_expression.operator().token().r#type(),
+        to_s_expression(binary_expression.left_operand()),
+        to_s_expression(binary_expression.right_operand())
+      ),
+
+      Expression::Grouping(grouping) => to_s_expression(grouping.inner())
+    }
+  }
+
+  // Locks down the precedance hierarchy : for each pair of adjacent precedance levels, the
+  // tighter binding operator must end up deeper in the tree, regardless of the order in which the
+  // operators appear.
+  #[test]
+  fn precedance_ordering() {
+    let cases = [
+      // unary > multiplicative
+      ("-a * b", "(* (- a) b)"),
+      ("a * -b", "(* a (- b))"),
+      ("!a / b", "(/ (! a) b)"),
+      ("not a and b", "(and (not a) b)"),
+      ("not a == b", "(== (not a) b)"),
+      // multiplicative > additive
+      ("a + b * c", "(+ a (* b c))"),
+      ("a * b - c", "(- (* a b) c)"),
+      ("a + b % c", "(+ a (% b c))"),
+      ("1 + 10 % 3 * 2", "(+ 1 (* (% 10 3) 2))"),
+      ("7 div 2 == 3", "(== (div 7 2) 3)"),
+      // additive > comparison
+      ("a < b + c", "(< a (+ b c))"),
+      ("a - b >= c", "(>= (- a b) c)"),
+      // comparison > equality
+      ("a == b > c", "(== a (> b c))"),
+      ("a <= b != c", "(!= (<= a b) c)"),
+      // equality > and > or
+      ("a == b and c", "(and (== a b) c)"),
+      ("a and b or c", "(or (and a b) c)"),
+      ("a or b and c", "(or a (and b c))"),
+      // Binary operators are left associative.
+      ("a - b - c", "(- (- a b) c)"),
+      ("a == b != c", "(!= (== a b) c)"),
+      ("a or b or c", "(or (or a b) c)"),
+      // Unary operators are right associative.
+      ("!-a", "(! (- a))"),
+      ("not !a", "(not (! a))"),
+      // Paranthesis override the precedance.
+      ("(a + b) * c", "(* (+ a b) c)")
+    ];
+
+    for (source, expected) in cases {
+      let tokens = Lexer::new(source).lex().unwrap();
+      let expression = Parser::new(tokens).parse().unwrap();
+
+      assert_eq!(to_s_expression(&expression), expected, "source : {source}");
+    }
+  }
+
+  #[test]
+  fn modulo() {
+    let tokens = Lexer::new("5 % 2").lex().unwrap();
+    let expression = Parser::new(tokens).parse().unwrap();
+
+    let Expression::BinaryExpression(binary_expression) = *expression
+    else {
+      panic!("expected a binary expression");
+    };
+
+    assert!(matches!(
+      binary_expression.operator().precedance(),
+      Precedance::Multiplicative(Multiplicative::Modulo)
+    ));
+    // That's what the printer renders for the operator.
+    assert_eq!(
+      binary_expression.operator().precedance().to_string(),
+      "Modulo"
+    );
+  }
+
+  #[test]
+  fn errors_at_eof() {
+    let source = "1 + (2 * 3";
+
+    let tokens = Lexer::new(source).lex().unwrap();
+    let error = Parser::new(tokens).parse().unwrap_err();
+
+    assert!(matches!(
+      error.r#type(),
+      ErrorType::ExpectedCloseParanthesis
+    ));
+    assert_eq!(*error.position().index(), source.len());
+
+    let tokens = Lexer::new("1 +  ").lex().unwrap();
+    let error = Parser::new(tokens).parse().unwrap_err();
+
+    assert!(matches!(error.r#type(), ErrorType::ExpectedLiteral));
+    assert_eq!(error.position().to_string(), "line 1, column 6");
+  }
+
+  #[test]
+  fn statements() {
+    let tokens = Lexer::new("print 1 + 2;\n1 + 2;").lex().unwrap();
+    let statements = Parser::new(tokens).parse_program().unwrap();
+
+    assert_eq!(statements.len(), 2);
+    assert!(matches!(
+      &statements[0],
+      Statement::Print(expression) if to_s_expression(expression) == "(+ 1 2)"
+    ));
+    assert!(matches!(
+      &statements[1],
+      Statement::Expression(expression) if to_s_expression(expression) == "(+ 1 2)"
+    ));
+
+    // An empty program has no statements.
+    let tokens = Lexer::new("// nothing").lex().unwrap();
+    assert!(Parser::new(tokens).parse_program().unwrap().is_empty());
+  }
+
+  #[test]
+  fn expected_semicolon() {
+    let tokens = Lexer::new("print 1\nprint 2;").lex().unwrap();
+    let error = Parser::new(tokens).parse_program().unwrap_err();
+
+    // Reported at the token following the expression.
+    assert!(matches!(error.r#type(), ErrorType::ExpectedSemicolon));
+    assert_eq!(error.position().to_line_col(), (2, 1));
+
+    let tokens = Lexer::new("1 + 2").lex().unwrap();
+    let error = Parser::new(tokens).parse_program().unwrap_err();
+    assert!(matches!(error.r#type(), ErrorType::ExpectedSemicolon));
+    assert_eq!(error.position().to_line_col(), (1, 6));
+  }
+
+  #[test]
+  fn misspelled_keyword() {
+    let tokens = Lexer::new("print 1;\nfnu add() {}").lex().unwrap();
+    let error = Parser::new(tokens).parse_program().unwrap_err();
+
+    // Reported at the misspelled keyword, rather than where the semicolon was expected.
+    assert!(matches!(
+      error.r#type(),
+      ErrorType::MisspelledKeyword(Keyword::Fun)
+    ));
+    assert_eq!(error.position().to_line_col(), (2, 1));
+    assert_eq!(error.r#type().to_string(), "unknown; did you mean 'fun'?");
+
+    // Also when the identifier is interned.
+    let mut lexer = Lexer::new("fnu add() {}").with_interner(Interner::default());
+    let tokens = lexer.lex().unwrap();
+    let interner = lexer.into_interner().unwrap();
+
+    let error = Parser::new(tokens)
+      .with_interner(&interner)
+      .parse_program()
+      .unwrap_err();
+    assert!(matches!(
+      error.r#type(),
+      ErrorType::MisspelledKeyword(Keyword::Fun)
+    ));
+
+    // Short identifiers are taken as is.
+    let tokens = Lexer::new("i x").lex().unwrap();
+    let error = Parser::new(tokens).parse_program().unwrap_err();
+    assert!(matches!(error.r#type(), ErrorType::ExpectedSemicolon));
+  }
+
+  #[test]
+  fn trailing_tokens() {
+    let tokens = Lexer::new("1 2").lex().unwrap();
+    let error = Parser::new(tokens).parse().unwrap_err();
+
+    assert!(matches!(error.r#type(), ErrorType::UnexpectedToken));
+    assert_eq!(error.position().to_line_col(), (1, 3));
+
+    let tokens = Lexer::new("(1 + 2))").lex().unwrap();
+    let error = Parser::new(tokens).parse().unwrap_err();
+    assert!(matches!(error.r#type(), ErrorType::UnexpectedToken));
+    assert_eq!(error.position().to_line_col(), (1, 8));
+  }
+
+  #[test]
+  fn display_error() {
+    let tokens = Lexer::new("(1").lex().unwrap();
+    let error = Parser::new(tokens).parse().unwrap_err();
+
+    assert!(matches!(
+      error.r#type(),
+      ErrorType::ExpectedCloseParanthesis
+    ));
+    assert_eq!(
+      error.to_string(),
+      "parse error at line 1, column 3: expected a close paranthesis"
+    );
+  }
+
+  #[test]
+  fn render_named_error() {
+    let source = "(1";
+    let error = Parser::from_lexer(Lexer::new(source)).parse().unwrap_err();
+
+    assert_eq!(
+      error.render_named(source, Some("main.lox")),
+      "error: expected a close paranthesis\n  --> main.lox:1:3\n   | (1\n   |   ^\n"
+    );
+  }
+
+  #[test]
+  fn parse_from_lexer() {
+    let expression = Parser::from_lexer(Lexer::new("1 + 2 * 3")).parse().unwrap();
+    assert_eq!(to_s_expression(&expression), "(+ 1 (* 2 3))");
+
+    // Fails fast at the first lexer error, before reaching the unterminated string.
+    let error = Parser::from_lexer(Lexer::new("1 + ^ + \"never ends"))
+      .parse()
+      .unwrap_err();
+
+    assert!(matches!(
+      error.r#type(),
+      ErrorType::LexError(lexer::ErrorType::InvalidCharacter)
+    ));
+    assert_eq!(error.r#type().to_string(), "invalid character");
+    assert_eq!(error.position().to_string(), "line 1, column 5");
   }
 }
 
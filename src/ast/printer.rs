@@ -1,14 +1,25 @@
-use crate::ast::Expression;
+use crate::ast::{Expression, operator::Operator};
 
 pub struct Printer;
 
 impl<'expression> Printer {
   pub fn print(expression: &Expression<'expression>) {
     println!("root");
-    Self::inner(expression, "", true);
+    Self::inner(expression, None, "", true);
   }
 
-  fn inner(expression: &Expression<'expression>, prefix: &str, is_last_child: bool) {
+  // Like print, but renders operators exactly as they were written in the given source.
+  pub fn print_with_source(expression: &Expression<'expression>, source: &str) {
+    println!("root");
+    Self::inner(expression, Some(source), "", true);
+  }
+
+  fn inner(
+    expression: &Expression<'expression>,
+    source: Option<&str>,
+    prefix: &str,
+    is_last_child: bool
+  ) {
     // Determine the indentation that visually connects this node with the parent node.
     let connector = if !is_last_child { "├── " } else { "└── " };
 
@@ -19,28 +30,48 @@ impl<'expression> Printer {
 
       Expression::UnaryExpression(unary_expression) => {
         // Print the unary operator.
-        let unary_operator_type = unary_expression.operator.precedance();
-        println!("{prefix}{connector}{unary_operator_type}");
+        let unary_operator = Self::operator(&unary_expression.operator, source);
+        println!("{prefix}{connector}{unary_operator}");
 
         // Print the operand as a child node.
 
         let child_prefix = format!("{prefix}{}", if is_last_child { "    " } else { "│   " });
 
-        Self::inner(&unary_expression.operand, &child_prefix, true);
+        Self::inner(&unary_expression.operand, source, &child_prefix, true);
       }
 
       Expression::BinaryExpression(binary_expression) => {
         // Print the binary operator.
-        let binary_operator_type = binary_expression.operator.precedance();
-        println!("{prefix}{connector}{binary_operator_type}");
+        let binary_operator = Self::operator(&binary_expression.operator, source);
+        println!("{prefix}{connector}{binary_operator}");
 
         // Print the operands as child nodes.
 
         let child_prefix = format!("{prefix}{}", if is_last_child { "    " } else { "│   " });
 
-        Self::inner(&binary_expression.left_operand, &child_prefix, false);
-        Self::inner(&binary_expression.right_operand, &child_prefix, true);
+        Self::inner(
+          &binary_expression.left_operand,
+          source,
+          &child_prefix,
+          false
+        );
+        Self::inner(
+          &binary_expression.right_operand,
+          source,
+          &child_prefix,
+          true
+        );
       }
+
+      // The paranthesis are already implied by the shape of the tree.
+      Expression::Grouping(grouping) => Self::inner(&grouping.inner, source, prefix, is_last_child)
+    }
+  }
+
+  fn operator(operator: &Operator, source: Option<&str>) -> String {
+    match source {
+      Some(source) => operator.lexeme(source).to_string(),
+      None => operator.precedance().to_string()
     }
   }
 }
@@ -0,0 +1,12 @@
+use crate::ast::Expression;
+
+// Programs are sequences of statements. Unlike an expression, a statement doesn't evaluate to a
+// value, but is executed for its side effects.
+#[derive(Debug)]
+pub enum Statement<'statement> {
+  // An expression, whose value gets discarded, like a function call.
+  Expression(Box<Expression<'statement>>),
+
+  // Evaluates the expression and prints its value.
+  Print(Box<Expression<'statement>>)
+}
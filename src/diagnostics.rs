@@ -0,0 +1,153 @@
+use {
+  crate::{
+    ast::parser,
+    lexer::{self, source::Span}
+  },
+  getset::Getters
+};
+
+// A machine readable form of the errors reported by each stage, for editor integrations. Unlike
+// the error messages, the codes are stable, so tools can rely on them.
+#[derive(Debug, PartialEq, Eq, Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Diagnostic {
+  // Like L0001 for a lexer error, or P0002 for a parser error.
+  #[getset(get = "pub")]
+  code: &'static str,
+
+  #[getset(get = "pub")]
+  severity: Severity,
+
+  #[getset(get = "pub")]
+  span: Span,
+
+  #[getset(get = "pub")]
+  message: String
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[strum(serialize_all = "lowercase")]
+pub enum Severity {
+  Error,
+  Warning
+}
+
+#[cfg(feature = "serde")]
+impl Diagnostic {
+  pub fn to_json(&self) -> String {
+    serde_json::to_string(self).expect("a diagnostic is always serializable")
+  }
+}
+
+// The span covers the offending lexeme or token, so that an editor can highlight it.
+impl From<lexer::Error> for Diagnostic {
+  fn from(error: lexer::Error) -> Self {
+    Self {
+      code:     error.r#type().code(),
+      severity: Severity::Error,
+      span:     *error.span(),
+      message:  error.r#type().to_string()
+    }
+  }
+}
+
+impl From<parser::Error> for Diagnostic {
+  fn from(error: parser::Error) -> Self {
+    Self {
+      code:     error.r#type().code(),
+      severity: Severity::Error,
+      span:     *error.span(),
+      message:  error.r#type().to_string()
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::{ast::parser::Parser, lexer::Lexer},
+    std::collections::HashSet,
+    strum::IntoEnumIterator
+  };
+
+  #[test]
+  fn codes_never_collide() {
+    let lexer_codes = lexer::ErrorType::iter()
+      .map(|r#type| r#type.code())
+      .collect::<Vec<_>>();
+
+    // Lexer errors encountered while parsing keep their own code.
+    let parser_codes = parser::ErrorType::iter()
+      .filter(|r#type| !matches!(r#type, parser::ErrorType::LexError(_)))
+      .map(|r#type| r#type.code())
+      .collect::<Vec<_>>();
+
+    let codes = lexer_codes
+      .iter()
+      .chain(&parser_codes)
+      .collect::<HashSet<_>>();
+    assert_eq!(codes.len(), lexer_codes.len() + parser_codes.len());
+
+    assert!(lexer_codes.iter().all(|code| code.starts_with('L')));
+    assert!(parser_codes.iter().all(|code| code.starts_with('P')));
+  }
+
+  #[test]
+  fn from_lexer_error() {
+    let errors = Lexer::new("1 + @").lex().unwrap_err();
+    let diagnostic = Diagnostic::from(errors.into_iter().next().unwrap());
+
+    assert_eq!(
+      diagnostic,
+      Diagnostic {
+        code:     "L0001",
+        severity: Severity::Error,
+        span:     Span::new(4, 5),
+        message:  "invalid character".to_string()
+      }
+    );
+  }
+
+  #[test]
+  fn from_parser_error() {
+    let error = Parser::new(Lexer::new("(1 + 2").lex().unwrap())
+      .parse()
+      .unwrap_err();
+    let diagnostic = Diagnostic::from(error);
+
+    assert_eq!(*diagnostic.code(), "P0002");
+    assert_eq!(*diagnostic.span(), Span::new(6, 6));
+    assert_eq!(diagnostic.message(), "expected a close paranthesis");
+  }
+
+  #[test]
+  fn spans_the_offending_token() {
+    let error = Parser::new(Lexer::new("1 22").lex().unwrap())
+      .parse()
+      .unwrap_err();
+
+    assert_eq!(*Diagnostic::from(error).span(), Span::new(2, 4));
+  }
+
+  #[test]
+  fn lex_errors_while_parsing_keep_their_code() {
+    let error = Parser::from_lexer(Lexer::new("1 + @")).parse().unwrap_err();
+
+    assert_eq!(*Diagnostic::from(error).code(), "L0001");
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn to_json() {
+    let errors = Lexer::new("1 + @").lex().unwrap_err();
+    let diagnostic = Diagnostic::from(errors.into_iter().next().unwrap());
+
+    assert_eq!(
+      diagnostic.to_json(),
+      r#"{"code":"L0001","severity":"error","span":{"start":4,"end":5},"message":"invalid character"}"#
+    );
+  }
+}
@@ -0,0 +1,71 @@
+use std::{collections::HashMap, fmt::Display};
+
+// Cheap handle to an interned string. Comparing two symbols is an integer comparison, instead of
+// a string comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Symbol(u32);
+
+impl Display for Symbol {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(formatter, "#{}", self.0)
+  }
+}
+
+// Stores a single copy of each distinct string, handing out the same Symbol every time the same
+// string is interned.
+#[derive(Debug, Default)]
+pub struct Interner {
+  symbols: HashMap<String, Symbol>,
+
+  // Indexed by the symbol.
+  strings: Vec<String>
+}
+
+impl Interner {
+  pub fn intern(&mut self, string: &str) -> Symbol {
+    if let Some(symbol) = self.symbols.get(string) {
+      return *symbol;
+    }
+
+    let symbol = Symbol(self.strings.len() as u32);
+    self.symbols.insert(string.to_string(), symbol);
+    self.strings.push(string.to_string());
+
+    symbol
+  }
+
+  // Panics, if the symbol wasn't handed out by this interner.
+  pub fn resolve(&self, symbol: Symbol) -> &str {
+    &self.strings[symbol.0 as usize]
+  }
+
+  // Number of distinct strings interned.
+  pub fn len(&self) -> usize {
+    self.strings.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.strings.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn same_string_same_symbol() {
+    let mut interner = Interner::default();
+
+    let counter = interner.intern("counter");
+    let total = interner.intern("total");
+
+    assert_ne!(counter, total);
+    assert_eq!(interner.intern("counter"), counter);
+
+    assert_eq!(interner.resolve(counter), "counter");
+    assert_eq!(interner.resolve(total), "total");
+    assert_eq!(interner.len(), 2);
+  }
+}
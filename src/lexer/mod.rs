@@ -1,11 +1,16 @@
 use {
   crate::lexer::{
-    source::{Position, Source},
+    interner::Interner,
+    source::{Position, Source, Span},
     token::{Keyword, Token, TokenType}
   },
-  itertools::Itertools
+  getset::Getters,
+  itertools::Itertools,
+  std::{borrow::Cow, fmt::Display, ops::ControlFlow}
 };
 
+pub mod interner;
+pub mod reader;
 pub mod source;
 pub mod token;
 
@@ -13,20 +18,119 @@ pub mod token;
 // chunks we call tokens. These are the meaningful “words” and “punctuation” that make up the
 // language’s grammar.
 pub struct Lexer<'lexer> {
-  source: Source<'lexer>
+  source:  Source<'lexer>,
+  options: LexerOptions,
+
+  // Whether the EOF token has been emitted.
+  reached_eof: bool,
+
+  // Whether the source can start with a shebang line. Not the case, when lexing a piece of a
+  // larger source, which doesn't start at its beginning.
+  allows_shebang: bool,
+
+  // When present, identifiers get interned into it, and are emitted as symbols.
+  interner: Option<Interner>
+}
+
+// Opt-in deviations from the Lox lexical grammar.
+#[derive(Debug, Clone)]
+pub struct LexerOptions {
+  // Accept numbers with a leading or trailing decimal point, like .5 and 5.
+  pub lenient_decimals: bool,
+
+  // Which characters an identifier can start with, and which ones can follow. Lets DSLs built on
+  // this lexer accept identifiers like foo-bar or valid?.
+  pub is_identifier_start:    fn(char) -> bool,
+  pub is_identifier_continue: fn(char) -> bool,
+
+  // Marker which starts a line comment, like # for shell-like DSLs. Block comments always use
+  // /* ... */.
+  pub line_comment: &'static str,
+
+  // Number of columns a tab advances to the next tab stop by, in the reported positions. 1 by
+  // default, so that a tab counts as a single character.
+  pub tab_width: usize,
+
+  // Emit whitespaces and comments as tokens, instead of skipping them. Useful for tools like
+  // formatters, which need to reproduce the source.
+  pub keep_trivia: bool
+}
+
+impl Default for LexerOptions {
+  fn default() -> Self {
+    Self {
+      lenient_decimals:       false,
+      is_identifier_start:    |character| character.is_alphabetic() || (character == '_'),
+      is_identifier_continue: |character| character.is_alphanumeric() || (character == '_'),
+      line_comment:           "//",
+      tab_width:              1,
+      keep_trivia:            false
+    }
+  }
 }
 
 impl<'lexer> Lexer<'lexer> {
   pub fn new(source: &'lexer str) -> Self {
+    Self::with_options(source, LexerOptions::default())
+  }
+
+  pub fn with_options(source: &'lexer str, options: LexerOptions) -> Self {
+    Self {
+      source: Source::new(source).with_tab_width(options.tab_width),
+      options,
+      reached_eof: false,
+      allows_shebang: true,
+      interner: None
+    }
+  }
+
+  // Lexes a piece of a larger source, which starts at the given position.
+  fn starting_at(self, start: &Position) -> Self {
     Self {
-      source: Source::new(source)
+      source: self.source.starting_at(start),
+      allows_shebang: *start.index() == 0,
+      ..self
     }
   }
 
+  // Emits identifiers as TokenType::InternedIdentifier, interning their names into the given
+  // interner, so that comparing them becomes an integer comparison.
+  pub fn with_interner(self, interner: Interner) -> Self {
+    Self {
+      interner: Some(interner),
+      ..self
+    }
+  }
+
+  pub fn interner(&self) -> Option<&Interner> {
+    self.interner.as_ref()
+  }
+
+  // Hands the interner back, once lexing is done, so that the symbols can be resolved.
+  pub fn into_interner(self) -> Option<Interner> {
+    self.interner
+  }
+
+  // Names the source, like with the path of the file it was read from. Errors can then be
+  // rendered along with the name, using Error::render_named.
+  pub fn with_name(self, name: &'lexer str) -> Self {
+    Self {
+      source: self.source.with_name(name),
+      ..self
+    }
+  }
+
+  pub fn name(&self) -> Option<&'lexer str> {
+    *self.source.name()
+  }
+
+  // Position of the next character to be lexed.
+  pub fn position(&self) -> &Position {
+    self.source.position()
+  }
+
   pub fn lex(&mut self) -> Result<Vec<Token<'lexer>>, Vec<Error>> {
-    // Even if an error occurs, we keep scanning. There may be other errors later in the program.
-    // It gives our users a better experience if we detect as many of those as possible in one go.
-    let (tokens, errors): (Vec<_>, Vec<_>) = self.by_ref().partition_result();
+    let (tokens, errors) = self.lex_with_recovery();
 
     if !errors.is_empty() {
       return Err(errors);
@@ -34,14 +138,166 @@ impl<'lexer> Lexer<'lexer> {
 
     Ok(tokens)
   }
+
+  // Like lex, but also returns the tokens that could be produced when there are errors. Useful for
+  // tooling, like a syntax highlighter, which should keep working on the rest of the file.
+  pub fn lex_with_recovery(&mut self) -> (Vec<Token<'lexer>>, Vec<Error>) {
+    // Even if an error occurs, we keep scanning. There may be other errors later in the program.
+    // It gives our users a better experience if we detect as many of those as possible in one go.
+    self.by_ref().partition_result()
+  }
+
+  // Streams the tokens to the given callbacks, instead of materializing them into a Vec. Useful
+  // for processing very large sources, where only some statistics are needed. Scanning stops
+  // early, if the token callback returns ControlFlow::Break.
+  pub fn for_each_token(
+    &mut self,
+    mut on_token: impl FnMut(&Token<'lexer>) -> ControlFlow<()>,
+    mut on_error: impl FnMut(&Error)
+  ) -> LexSummary {
+    let (mut tokens, mut errors) = (0, 0);
+
+    for result in self.by_ref() {
+      match result {
+        Ok(token) => {
+          if *token.r#type() != TokenType::Eof {
+            tokens += 1;
+          }
+
+          if on_token(&token).is_break() {
+            break;
+          }
+        }
+
+        Err(error) => {
+          errors += 1;
+          on_error(&error);
+        }
+      }
+    }
+
+    let position = self.source.position();
+
+    LexSummary {
+      tokens,
+      errors,
+      lines: position.line(),
+      bytes: *position.index()
+    }
+  }
+
+  // Lightweight check, used by the REPL to decide whether it should prompt for more input before
+  // even trying to parse. Delimiters inside string literals and comments are ignored.
+  pub fn delimiter_balance(source: &str) -> DelimiterBalance {
+    let mut source = Source::new(source);
+
+    let mut open_delimiters = Vec::new();
+
+    // Skip the shebang line, if any.
+    if source.source().starts_with("#!") {
+      while source.consume_if_not_character('\n') {}
+    }
+
+    while let Some((position, character)) = source.next() {
+      match character {
+        '"' => {
+          while let Some((_, character)) = source.next_if_not_character('"') {
+            // An escaped double quote doesn't end the string literal.
+            if character == '\\' {
+              source.next();
+            }
+          }
+
+          if !source.consume_if_character('"') {
+            // The string literal may continue on the next line.
+            open_delimiters.push(character);
+            break;
+          }
+        }
+
+        '/' if source.consume_if_character('/') => while source.consume_if_not_character('\n') {},
+
+        '/' if source.consume_if_character('*') => {
+          // Block comments nest.
+          let mut depth = 1;
+          while let Some((_, character)) = source.next() {
+            match character {
+              '/' if source.consume_if_character('*') => depth += 1,
+              '*' if source.consume_if_character('/') => depth -= 1,
+
+              _ => {}
+            }
+
+            if depth == 0 {
+              break;
+            }
+          }
+
+          if depth > 0 {
+            // The block comment may continue on the next line.
+            open_delimiters.push('*');
+            break;
+          }
+        }
+
+        '(' | '{' | '[' => open_delimiters.push(character),
+
+        ')' | '}' | ']' => {
+          let expected = open_delimiters.pop().map(|delimiter| match delimiter {
+            '(' => ')',
+            '{' => '}',
+            _ => ']'
+          });
+
+          if expected != Some(character) {
+            return DelimiterBalance::Mismatched {
+              position,
+              found: character,
+              expected
+            };
+          }
+        }
+
+        _ => {}
+      }
+    }
+
+    if open_delimiters.is_empty() {
+      return DelimiterBalance::Balanced;
+    }
+
+    DelimiterBalance::Incomplete {
+      unclosed: open_delimiters
+    }
+  }
 }
 
 impl<'lexer> Iterator for Lexer<'lexer> {
   type Item = Result<Token<'lexer>, Error>;
 
   fn next(&mut self) -> Option<Self::Item> {
-    // Ignore any leading whitespaces.
-    self.consume_whitespaces();
+    let result = self.lex_next()?;
+
+    // The erroneous part of the source spans from where the error was detected, till where the
+    // lexer stopped. For e.g., the whole of an unterminated string.
+    Some(result.map_err(|error| {
+      let span = Span::between(&error.position, self.source.position());
+      error.with_span(span, self.source.source())
+    }))
+  }
+}
+
+impl<'lexer> Lexer<'lexer> {
+  fn lex_next(&mut self) -> Option<Result<Token<'lexer>, Error>> {
+    // Ignore any leading whitespaces and comments, unless they need to be kept.
+    if self.options.keep_trivia {
+      if let Some(result) = self.lex_trivia() {
+        return Some(result);
+      }
+    }
+    else if let Err(error) = self.consume_whitespaces_and_comments() {
+      return Some(Err(error));
+    }
 
     /*
       We go through the list of characters and group them together into the smallest sequence that
@@ -63,12 +319,15 @@ impl<'lexer> Iterator for Lexer<'lexer> {
       is a token.
     */
 
-    let character = self.source.peek()?;
+    let Some(character) = self.source.peek().copied()
+    else {
+      return self.lex_eof();
+    };
 
     match character {
       '"' => self.lex_string(),
-      _ if character.is_numeric() => self.lex_number(),
-      _ if character.is_alphabetic() => self.lex_keyword_or_identifier(),
+      _ if character.is_ascii_digit() => self.lex_number(),
+      _ if (self.options.is_identifier_start)(character) => self.lex_keyword_or_identifier(),
 
       _ => self.lex_symbol()
     }
@@ -76,116 +335,374 @@ impl<'lexer> Iterator for Lexer<'lexer> {
 }
 
 impl<'lexer> Lexer<'lexer> {
+  // Emits exactly one EOF token, after all the real tokens.
+  fn lex_eof(&mut self) -> Option<Result<Token<'lexer>, Error>> {
+    if self.reached_eof {
+      return None;
+    }
+    self.reached_eof = true;
+
+    let position = *self.source.position();
+    let token = Token::new(
+      TokenType::Eof,
+      position,
+      Span::between(&position, &position)
+    );
+    Some(Ok(token))
+  }
+
   fn lex_string(&mut self) -> Option<Result<Token<'lexer>, Error>> {
     // Consume the opening double quote.
     let (start, _) = self.source.next_if_character('"')?;
 
-    while self.source.consume_if_not_character('"') {}
+    // Stays None, as long as no escape sequence is encountered. That way, the literal value can
+    // just borrow from the source.
+    let mut unescaped: Option<String> = None;
 
-    // Determine the literal value.
-    let value = &(self.source.source())[(*start.index() + 1)..*self.source.position().index()];
-
-    // Try consuming the closing double quote.
-    match self.source.next_if_character('"') {
-      // Closing double quote not present.
-      // So, we've encountered an unterminated string.
-      None => Some(Err(Error {
-        position: start,
-        r#type:   ErrorType::UnterminatedString
-      })),
-
-      Some(_) => {
-        let token = Token::new(TokenType::String(value), start);
-        Some(Ok(token))
+    // Byte offset from where the part of the literal value, not yet copied to unescaped, starts.
+    let mut segment_start = *start.index() + 1;
+
+    // Position of the backslash, starting the first invalid escape sequence (if any).
+    let mut invalid_escape_sequence = None;
+
+    let end = loop {
+      match self.source.next() {
+        // Closing double quote not present.
+        // So, we've encountered an unterminated string.
+        None => {
+          return Some(Err(Error::new(start, ErrorType::UnterminatedString)));
+        }
+
+        Some((position, '"')) => break position,
+
+        // Line terminators are normalized to a \n in the literal value.
+        Some((position, '\n')) if self.source.source()[*position.index()..].starts_with('\r') => {
+          let segment = &(self.source.source())[segment_start..*position.index()];
+
+          let unescaped = unescaped.get_or_insert_with(String::new);
+          unescaped.push_str(segment);
+          unescaped.push('\n');
+
+          segment_start = *self.source.position().index();
+        }
+
+        Some((backslash, '\\')) => {
+          let segment = &(self.source.source())[segment_start..*backslash.index()];
+
+          // An escaped character, if any, gets consumed even when it's invalid, so that an
+          // escaped double quote doesn't end the string.
+          let escaped = self
+            .source
+            .next()
+            .and_then(|(_, character)| unescape(character));
+
+          match escaped {
+            Some(character) => {
+              let unescaped = unescaped.get_or_insert_with(String::new);
+              unescaped.push_str(segment);
+              unescaped.push(character);
+            }
+
+            None => {
+              invalid_escape_sequence.get_or_insert(backslash);
+            }
+          }
+
+          segment_start = *self.source.position().index();
+        }
+
+        _ => {}
       }
+    };
+
+    if let Some(position) = invalid_escape_sequence {
+      return Some(Err(Error::new(position, ErrorType::InvalidEscapeSequence)));
     }
+
+    // Determine the literal value.
+    let segment = &(self.source.source())[segment_start..*end.index()];
+    let value = match unescaped {
+      None => Cow::Borrowed(segment),
+
+      Some(mut unescaped) => {
+        unescaped.push_str(segment);
+        Cow::Owned(unescaped)
+      }
+    };
+
+    let span = Span::between(&start, self.source.position());
+    let token = Token::new(TokenType::String(value), start, span);
+    Some(Ok(token))
   }
 
   fn lex_number(&mut self) -> Option<Result<Token<'lexer>, Error>> {
     // Consume the integral part.
 
-    let (start, _) = self.source.next_if(|character| character.is_numeric())?;
+    let (start, first_digit) = self
+      .source
+      .next_if(|character| character.is_ascii_digit())?;
 
-    while self.source.consume_if(|character| character.is_numeric()) {}
+    if (first_digit == '0')
+      && self
+        .source
+        .consume_if(|character| matches!(character, 'x' | 'X'))
+    {
+      return self.lex_hexadecimal_number(start);
+    }
+
+    if let Some(error) = self.consume_digits() {
+      return Some(Err(error));
+    }
 
     // Try consuming the decimal.
-    // Note that, we don’t allow a leading or trailing decimal point.
-    if self.source.consume_if_character('.') {
-      // Consume the fractional part.
+    // Note that, unless lenient decimals are enabled, we don’t allow a leading or trailing decimal
+    // point.
+    if self.source.consume_if_character('.')
+      && let Some(error) = self.consume_fraction(start)
+    {
+      return Some(Err(error));
+    }
+
+    self.finish_number(start)
+  }
+
+  // Consumes the fractional part of the number starting at the given position, whose decimal
+  // point has already been consumed.
+  fn consume_fraction(&mut self, start: Position) -> Option<Error> {
+    if let Some(error) = self.consume_digit_separator_error() {
+      return Some(error);
+    }
+
+    if self
+      .source
+      .next_if(|character| character.is_ascii_digit())
+      .is_none()
+      && !self.options.lenient_decimals
+    {
+      if let Some(error) = self.consume_non_ascii_digits() {
+        return Some(error);
+      }
+
+      // No numeric character present.
+      // Which means the number has no fractional part.
+      return Some(Error::new(start, ErrorType::NumberHasNoFractionalPart));
+    };
+
+    self.consume_digits()
+  }
+
+  // Lexes an integer literal like 0x1F, whose 0x prefix has already been consumed.
+  fn lex_hexadecimal_number(&mut self, start: Position) -> Option<Result<Token<'lexer>, Error>> {
+    let digits_start = *self.source.position().index();
+
+    while self
+      .source
+      .consume_if(|character| character.is_ascii_hexdigit())
+    {}
+
+    let digits = &(self.source.source())[digits_start..*self.source.position().index()];
+    if digits.is_empty() {
+      return Some(Err(Error::new(start, ErrorType::MissingHexadecimalDigits)));
+    }
+
+    match i64::from_str_radix(digits, 16) {
+      Err(_) => Some(Err(Error::new(start, ErrorType::FailedParsingNumber))),
+
+      Ok(value) => {
+        let span = Span::between(&start, self.source.position());
+        let token = Token::new(TokenType::Number((value as f64).into()), start, span);
+        Some(Ok(token))
+      }
+    }
+  }
+
+  // Consumes the rest of a run of ASCII digits, which may be separated by single underscores (like
+  // 1_000_000). An underscore must be followed by a digit.
+  fn consume_digits(&mut self) -> Option<Error> {
+    loop {
+      while self
+        .source
+        .consume_if(|character| character.is_ascii_digit())
+      {}
+
+      if self.source.peek() != Some(&'_') {
+        return None;
+      }
 
       if self
         .source
-        .next_if(|character| character.is_numeric())
-        .is_none()
+        .peek_second()
+        .is_some_and(|character| character.is_ascii_digit())
       {
-        // No numeric character present.
-        // Which means the number has no fractional part.
-        return Some(Err(Error {
-          position: start,
-          r#type:   ErrorType::NumberHasNoFractionalPart
-        }));
-      };
+        self.source.next();
+        continue;
+      }
 
-      while self.source.consume_if(|character| character.is_numeric()) {}
+      return self.consume_digit_separator_error();
     }
+  }
 
-    // Determine the literal value.
+  // If the next character is a misplaced digit separator (trailing, doubled, or adjacent to the
+  // decimal point), consumes the rest of the malformed digits and points at the underscore.
+  fn consume_digit_separator_error(&mut self) -> Option<Error> {
+    let (position, _) = self.source.next_if_character('_')?;
+
+    while self
+      .source
+      .consume_if(|character| character.is_ascii_digit() || (*character == '_'))
+    {}
 
+    Some(Error::new(position, ErrorType::InvalidDigitSeparator))
+  }
+
+  // Determines the literal value of the number, which starts at the given position and has been
+  // consumed till the current position.
+  fn finish_number(&mut self, start: Position) -> Option<Result<Token<'lexer>, Error>> {
+    if let Some(error) = self
+      .consume_exponent()
+      .or_else(|| self.consume_non_ascii_digits())
+    {
+      return Some(Err(error));
+    }
+
+    // The digit separators need to be stripped, before parsing.
     let value = &(self.source.source())[*start.index()..*self.source.position().index()];
-    match value.parse() {
-      Err(_) => Some(Err(Error {
-        position: start,
-        r#type:   ErrorType::FailedParsingNumber
-      })),
+    match value.replace('_', "").parse::<f64>() {
+      // A number too large to be represented (like 1e400) overflows to infinity, which we don't
+      // accept as a literal. Whereas, a number too small (like 1e-400) just rounds to 0.
+      Err(_) => Some(Err(Error::new(start, ErrorType::FailedParsingNumber))),
+      Ok(value) if value.is_infinite() =>
+        Some(Err(Error::new(start, ErrorType::FailedParsingNumber))),
 
       Ok(value) => {
-        let token = Token::new(TokenType::Number(value), start);
+        let span = Span::between(&start, self.source.position());
+        let token = Token::new(TokenType::Number(value.into()), start, span);
         Some(Ok(token))
       }
     }
   }
 
+  // Consumes the exponent (like e10, e-3 or E+23) of a number literal, if any. The exponent marker
+  // must be followed by an optional sign and at least one digit.
+  fn consume_exponent(&mut self) -> Option<Error> {
+    let (marker, _) = self
+      .source
+      .next_if(|character| matches!(character, 'e' | 'E'))?;
+
+    self
+      .source
+      .consume_if(|character| matches!(character, '+' | '-'));
+
+    if self
+      .source
+      .peek()
+      .is_none_or(|character| !character.is_ascii_digit())
+    {
+      // Consume the rest of what looks like an identifier, so that something like 1else doesn't
+      // get split into a number and an identifier.
+      let is_identifier_continue = self.options.is_identifier_continue;
+      while self
+        .source
+        .consume_if(|character| is_identifier_continue(*character))
+      {}
+
+      return Some(Error::new(marker, ErrorType::MissingExponent));
+    }
+
+    self.consume_digits()
+  }
+
+  // Unicode digits, other than the ASCII ones (like ٣), aren't allowed in number literals.
+  // If they follow a number literal, we consume them and point at the first one.
+  fn consume_non_ascii_digits(&mut self) -> Option<Error> {
+    let (position, _) = self.source.next_if(|character| character.is_numeric())?;
+
+    while self.source.consume_if(|character| character.is_numeric()) {}
+
+    Some(Error::new(position, ErrorType::NonAsciiDigitInNumber))
+  }
+
   fn lex_keyword_or_identifier(&mut self) -> Option<Result<Token<'lexer>, Error>> {
-    // The first character must be an alphabet.
-    let (start, _) = self.source.next_if(|character| character.is_alphabetic())?;
+    let LexerOptions {
+      is_identifier_start,
+      is_identifier_continue,
+      ..
+    } = self.options;
+
+    // By default, the first character must be an alphabet or an underscore.
+    let (start, _) = self
+      .source
+      .next_if(|character| is_identifier_start(*character))?;
 
     while self
       .source
-      .consume_if(|character| character.is_alphanumeric() || (*character == '_'))
+      .consume_if(|character| is_identifier_continue(*character))
     {}
 
     let value = &(self.source.source())[(*start.index())..(*self.source.position().index())];
 
+    // Something like _1 or _1_000 is a number with a misplaced digit separator, rather than an
+    // identifier. Whereas, something like _foo or _ still is an identifier.
+    if let Some(rest) = value.strip_prefix('_')
+      && rest.contains(|character: char| character.is_ascii_digit())
+      && rest
+        .chars()
+        .all(|character| character.is_ascii_digit() || (character == '_'))
+    {
+      return Some(Err(Error::new(start, ErrorType::InvalidDigitSeparator)));
+    }
+
+    let span = Span::between(&start, self.source.position());
+
     let token = match Keyword::try_from(value) {
-      Ok(keyword) => Token::new(TokenType::Keyword(keyword), start),
+      Ok(keyword) => Token::new(TokenType::Keyword(keyword), start, span),
+
+      _ => match &mut self.interner {
+        Some(interner) => Token::new(
+          TokenType::InternedIdentifier(interner.intern(value)),
+          start,
+          span
+        ),
 
-      _ => Token::new(TokenType::Identifier(value), start)
+        None => Token::new(TokenType::Identifier(value), start, span)
+      }
     };
 
     Some(Ok(token))
   }
 
   fn lex_symbol(&mut self) -> Option<Result<Token<'lexer>, Error>> {
-    let (mut position, mut character) = self.source.next()?;
-
-    // Ignore any comments.
-    while (character == '/') && self.source.consume_if_character('/') {
-      self.consume_comment();
-
-      (position, character) = self.source.next()?;
-    }
+    let (position, character) = self.source.next()?;
 
     macro_rules! make_token {
       ($token_type: expr) => {
-        Token::new($token_type, position)
+        Token::new(
+          $token_type,
+          position,
+          Span::between(&position, self.source.position())
+        )
       };
     }
 
     let token = match character {
+      // A number with a leading decimal point.
+      '.'
+        if self.options.lenient_decimals
+          && self
+            .source
+            .peek()
+            .is_some_and(|character| character.is_ascii_digit()) =>
+      {
+        if let Some(error) = self.consume_fraction(position) {
+          return Some(Err(error));
+        }
+        return self.finish_number(position);
+      }
+
       '(' => make_token!(TokenType::OpenParanthesis),
       ')' => make_token!(TokenType::CloseParanthesis),
       '{' => make_token!(TokenType::OpenBrace),
-      '}' => make_token!(TokenType::OpenBrace),
+      '}' => make_token!(TokenType::CloseBrace),
       ',' => make_token!(TokenType::Comma),
       '.' => make_token!(TokenType::Dot),
       ';' => make_token!(TokenType::Semicolon),
@@ -194,6 +711,7 @@ impl<'lexer> Lexer<'lexer> {
       '-' => make_token!(TokenType::Minus),
       '*' => make_token!(TokenType::Multiply),
       '/' => make_token!(TokenType::Divide),
+      '%' => make_token!(TokenType::Percent),
 
       '!' if self.source.consume_if_character('=') => make_token!(TokenType::NotEquals),
       '!' => make_token!(TokenType::Not),
@@ -206,16 +724,78 @@ impl<'lexer> Lexer<'lexer> {
       '=' => make_token!(TokenType::Assign),
 
       // We have encountered an unrecognized character.
-      _ =>
-        return Some(Err(Error {
-          r#type: ErrorType::InvalidCharacter,
-          position
-        })),
+      _ => return Some(Err(Error::new(position, ErrorType::InvalidCharacter)))
     };
 
     Some(Ok(token))
   }
 
+  fn consume_whitespaces_and_comments(&mut self) -> Result<(), Error> {
+    while self.consume_trivia()?.is_some() {}
+
+    Ok(())
+  }
+
+  // Emits the next run of whitespaces or comment as a token, instead of skipping it.
+  fn lex_trivia(&mut self) -> Option<Result<Token<'lexer>, Error>> {
+    let start = *self.source.position();
+
+    let trivia = match self.consume_trivia() {
+      Err(error) => return Some(Err(error)),
+      Ok(trivia) => trivia?
+    };
+
+    let span = Span::between(&start, self.source.position());
+    let lexeme = span.slice(self.source.source());
+
+    let token_type = match trivia {
+      Trivia::Whitespace => TokenType::Whitespace(lexeme),
+      Trivia::LineComment => TokenType::LineComment(lexeme),
+      Trivia::BlockComment => TokenType::BlockComment(lexeme)
+    };
+
+    Some(Ok(Token::new(token_type, start, span)))
+  }
+
+  // Consumes a single run of whitespaces or a single comment, if present.
+  fn consume_trivia(&mut self) -> Result<Option<Trivia>, Error> {
+    if self
+      .source
+      .peek()
+      .is_some_and(|character| character.is_whitespace())
+    {
+      self.consume_whitespaces();
+      return Ok(Some(Trivia::Whitespace));
+    }
+
+    let line_comment = self.options.line_comment;
+    let is_line_comment = !line_comment.is_empty()
+      && self.source.source()[*self.source.position().index()..].starts_with(line_comment);
+
+    Ok(Some(
+      match (self.source.peek().copied(), self.source.peek_second()) {
+        _ if is_line_comment => {
+          self.consume_comment();
+          Trivia::LineComment
+        }
+
+        (Some('/'), Some('*')) => {
+          self.consume_block_comment()?;
+          Trivia::BlockComment
+        }
+
+        // A shebang line, like #!/usr/bin/env lox, is only allowed at the very beginning of the
+        // source. It lets Lox scripts be run as executables.
+        (Some('#'), Some('!')) if self.allows_shebang && (*self.source.position().index() == 0) => {
+          self.consume_comment();
+          Trivia::LineComment
+        }
+
+        _ => return Ok(None)
+      }
+    ))
+  }
+
   #[inline]
   fn consume_whitespaces(&mut self) {
     while self
@@ -228,16 +808,154 @@ impl<'lexer> Lexer<'lexer> {
   fn consume_comment(&mut self) {
     while self.source.consume_if_not_character('\n') {}
   }
+
+  // Consumes a /* ... */ comment, which may span multiple lines. Block comments nest, so the
+  // comment only ends once every /* inside it has been closed by a matching */.
+  fn consume_block_comment(&mut self) -> Result<(), Error> {
+    let start = *self.source.position();
+
+    // Consume the opening /*.
+    self.source.nth(1);
+
+    let mut depth = 1;
+    while depth > 0 {
+      match self.source.next() {
+        None => {
+          return Err(Error::new(start, ErrorType::UnterminatedBlockComment));
+        }
+
+        Some((_, '/')) if self.source.consume_if_character('*') => depth += 1,
+        Some((_, '*')) if self.source.consume_if_character('/') => depth -= 1,
+
+        _ => {}
+      }
+    }
+
+    Ok(())
+  }
+}
+
+// Returns the character an escape sequence (without the leading backslash) stands for, or None if
+// the escape sequence is invalid.
+fn unescape(character: char) -> Option<char> {
+  Some(match character {
+    '"' => '"',
+    '\\' => '\\',
+    'n' => '\n',
+    't' => '\t',
+    'r' => '\r',
+    '0' => '\0',
+
+    _ => return None
+  })
+}
+
+enum Trivia {
+  Whitespace,
+  LineComment,
+  BlockComment
+}
+
+#[derive(Debug, Getters)]
+pub struct LexSummary {
+  // Excluding the EOF token, which is still handed to the token callback.
+  #[getset(get = "pub")]
+  tokens: usize,
+
+  #[getset(get = "pub")]
+  errors: usize,
+
+  // Number of lines reached, including the one the lexer stopped at.
+  #[getset(get = "pub")]
+  lines: usize,
+
+  #[getset(get = "pub")]
+  bytes: usize
 }
 
 #[derive(Debug)]
+pub enum DelimiterBalance {
+  Balanced,
+
+  // More input is needed to close these delimiters (innermost last). An unterminated string
+  // literal shows up as a '"', and an unterminated block comment as a '*'.
+  Incomplete {
+    unclosed: Vec<char>
+  },
+
+  // A closing delimiter which doesn't match the innermost open delimiter (if any). No amount of
+  // additional input can fix this.
+  Mismatched {
+    position: Position,
+    found:    char,
+    expected: Option<char>
+  }
+}
+
+#[derive(Debug, Getters)]
 pub struct Error {
+  #[getset(get = "pub")]
   position: Position,
-  r#type:   ErrorType
+
+  #[getset(get = "pub")]
+  r#type: ErrorType,
+
+  // The offending part of the source, which is at least a character wide, unless the error is at
+  // the end of the source.
+  #[getset(get = "pub")]
+  span: Span
 }
 
-#[derive(Debug, PartialEq, Eq, strum_macros::Display)]
+impl Error {
+  fn new(position: Position, r#type: ErrorType) -> Self {
+    Self {
+      position,
+      r#type,
+      span: Span::between(&position, &position)
+    }
+  }
+
+  // Widens the span to cover at least the character at the error's position.
+  pub(crate) fn with_span(self, span: Span, source: &str) -> Self {
+    let start = *span.start() as usize;
+    let end =
+      (*span.end() as usize).max(start + source[start..].chars().next().map_or(0, char::len_utf8));
+
+    Self {
+      span: Span::new(start as u32, end as u32),
+      ..self
+    }
+  }
+
+  // Renders the error, along with the offending line of source and a caret under the column.
+  pub fn render(&self, source: &str) -> String {
+    self.position.render(source, &self.r#type.to_string())
+  }
+
+  // Like render, but reports the position as name:line:column, when the source has a name.
+  pub fn render_named(&self, source: &str, name: Option<&str>) -> String {
+    self
+      .position
+      .render_named(source, name, &self.r#type.to_string())
+  }
+}
+
+impl Display for Error {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      formatter,
+      "lexer error at {}: {}",
+      self.position, self.r#type
+    )
+  }
+}
+
+impl std::error::Error for Error {}
+
+// Default only so that the parser's error types, which wrap it, can be iterated over.
+#[derive(Debug, Clone, Default, PartialEq, Eq, strum_macros::Display, strum_macros::EnumIter)]
 pub enum ErrorType {
+  #[default]
   #[strum(to_string = "invalid character")]
   InvalidCharacter,
 
@@ -248,12 +966,56 @@ pub enum ErrorType {
   NumberHasNoFractionalPart,
 
   #[strum(to_string = "failed parsing number")]
-  FailedParsingNumber
+  FailedParsingNumber,
+
+  #[strum(to_string = "non-ASCII digit in number literal")]
+  NonAsciiDigitInNumber,
+
+  #[strum(to_string = "invalid escape sequence")]
+  InvalidEscapeSequence,
+
+  #[strum(to_string = "unterminated block comment")]
+  UnterminatedBlockComment,
+
+  #[strum(to_string = "missing exponent in number literal")]
+  MissingExponent,
+
+  #[strum(to_string = "invalid digit separator")]
+  InvalidDigitSeparator,
+
+  #[strum(to_string = "hexadecimal number literal has no digits")]
+  MissingHexadecimalDigits,
+
+  // Either reading failed, or what was read isn't valid UTF-8.
+  #[strum(to_string = "failed reading source")]
+  FailedReadingSource
+}
+
+impl ErrorType {
+  // Stable code identifying the error, for tooling. Codes must never be reused or reassigned.
+  pub fn code(&self) -> &'static str {
+    match self {
+      ErrorType::InvalidCharacter => "L0001",
+      ErrorType::UnterminatedString => "L0002",
+      ErrorType::NumberHasNoFractionalPart => "L0003",
+      ErrorType::FailedParsingNumber => "L0004",
+      ErrorType::NonAsciiDigitInNumber => "L0005",
+      ErrorType::InvalidEscapeSequence => "L0006",
+      ErrorType::UnterminatedBlockComment => "L0007",
+      ErrorType::MissingExponent => "L0008",
+      ErrorType::InvalidDigitSeparator => "L0009",
+      ErrorType::MissingHexadecimalDigits => "L0010",
+      ErrorType::FailedReadingSource => "L0011"
+    }
+  }
 }
 
 #[cfg(test)]
 mod tests {
-  use super::*;
+  use {
+    super::*,
+    crate::{ast::parser::Parser, lexer::source::LineIndex}
+  };
 
   #[test]
   fn empty_source() {
@@ -262,7 +1024,118 @@ mod tests {
     let mut lexer = Lexer::new(source);
 
     let tokens = lexer.lex().unwrap();
-    assert!(tokens.is_empty());
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(*tokens[0].r#type(), TokenType::Eof);
+  }
+
+  #[test]
+  fn eof_after_trailing_trivia() {
+    let source = "1 + 2 // done\n  /* really */ ";
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+    // Exactly one EOF token, after all the real tokens.
+    assert_eq!(tokens.len(), 4);
+    assert_eq!(*tokens[3].r#type(), TokenType::Eof);
+    assert!(lexer.next().is_none());
+
+    let position = tokens[3].position();
+    assert_eq!(*position.index(), source.len());
+    assert_eq!(position.to_string(), "line 2, column 16");
+    assert!(tokens[3].span().slice(source).is_empty());
+  }
+
+  #[test]
+  fn eof_position() {
+    let source = "1 +\n22";
+    let tokens = Lexer::new(source).lex().unwrap();
+
+    // Just past the last character, with a zero width span there.
+    let eof = tokens.last().unwrap();
+    assert_eq!(*eof.r#type(), TokenType::Eof);
+    assert_eq!(eof.position().to_line_col(), (2, 3));
+    assert_eq!(*eof.position().index(), source.len());
+    assert_eq!(*eof.span(), Span::new(6, 6));
+  }
+
+  #[test]
+  fn braces() {
+    let tokens = Lexer::new("{}").lex().unwrap();
+
+    assert_eq!(*tokens[0].r#type(), TokenType::OpenBrace);
+    assert_eq!(*tokens[1].r#type(), TokenType::CloseBrace);
+  }
+
+  #[test]
+  fn symbols() {
+    let tokens = Lexer::new("(){},.;+-*/%! != = == > >= < <=").lex().unwrap();
+
+    let token_types = tokens
+      .iter()
+      .map(|token| token.r#type())
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      token_types,
+      [
+        &TokenType::OpenParanthesis,
+        &TokenType::CloseParanthesis,
+        &TokenType::OpenBrace,
+        &TokenType::CloseBrace,
+        &TokenType::Comma,
+        &TokenType::Dot,
+        &TokenType::Semicolon,
+        &TokenType::Plus,
+        &TokenType::Minus,
+        &TokenType::Multiply,
+        &TokenType::Divide,
+        &TokenType::Percent,
+        &TokenType::Not,
+        &TokenType::NotEquals,
+        &TokenType::Assign,
+        &TokenType::Equals,
+        &TokenType::GreaterThan,
+        &TokenType::GreaterThanOrEquals,
+        &TokenType::LessThan,
+        &TokenType::LessThanOrEquals,
+        &TokenType::Eof
+      ]
+    );
+  }
+
+  #[test]
+  fn trailing_comment() {
+    let tokens = Lexer::new("1 //comment").lex().unwrap();
+
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(*tokens[0].r#type(), TokenType::Number(1.0.into()));
+  }
+
+  #[test]
+  fn trailing_divide() {
+    let tokens = Lexer::new("1 /").lex().unwrap();
+
+    assert_eq!(tokens.len(), 3);
+    assert_eq!(*tokens[1].r#type(), TokenType::Divide);
+
+    let tokens = Lexer::new("/").lex().unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::Divide);
+  }
+
+  #[test]
+  fn comment_followed_by_newline() {
+    let tokens = Lexer::new("a//b\nc").lex().unwrap();
+
+    assert_eq!(tokens.len(), 3);
+    assert_eq!(*tokens[0].r#type(), TokenType::Identifier("a"));
+    assert_eq!(*tokens[1].r#type(), TokenType::Identifier("c"));
+
+    let tokens = Lexer::new("// one\n  // two\n\n 1 / 2 // three")
+      .lex()
+      .unwrap();
+    assert_eq!(tokens.len(), 4);
+    assert_eq!(*tokens[1].r#type(), TokenType::Divide);
   }
 
   #[test]
@@ -277,6 +1150,281 @@ mod tests {
     assert_eq!(error.r#type, ErrorType::InvalidCharacter);
   }
 
+  #[test]
+  fn non_ascii_digits() {
+    // Not a valid start of a number, nor of an identifier.
+    let errors = Lexer::new("٣").lex().unwrap_err();
+    assert_eq!(errors[0].r#type, ErrorType::InvalidCharacter);
+
+    let errors = Lexer::new("1٣").lex().unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].r#type, ErrorType::NonAsciiDigitInNumber);
+    assert_eq!(*errors[0].position.index(), 1);
+
+    let errors = Lexer::new("1.٣").lex().unwrap_err();
+    assert_eq!(errors[0].r#type, ErrorType::NonAsciiDigitInNumber);
+    assert_eq!(*errors[0].position.index(), 2);
+  }
+
+  #[test]
+  fn ascii_numbers() {
+    let tokens = Lexer::new("123 1.5").lex().unwrap();
+
+    assert_eq!(*tokens[0].r#type(), TokenType::Number(123.0.into()));
+    assert_eq!(*tokens[1].r#type(), TokenType::Number(1.5.into()));
+  }
+
+  #[test]
+  fn lenient_decimals() {
+    let options = LexerOptions {
+      lenient_decimals: true,
+      ..Default::default()
+    };
+
+    let tokens = Lexer::with_options(".5 == 0.5", options.clone())
+      .lex()
+      .unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::Number(0.5.into()));
+    assert_eq!(*tokens[0].r#type(), *tokens[2].r#type());
+    assert_eq!(tokens[0].span().slice(".5 == 0.5"), ".5");
+
+    let tokens = Lexer::with_options("5. == 5.0", options.clone())
+      .lex()
+      .unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::Number(5.0.into()));
+    assert_eq!(*tokens[0].r#type(), *tokens[2].r#type());
+    assert_eq!(tokens[0].span().slice("5. == 5.0"), "5.");
+
+    // Digit separators and exponents work the same, after a leading decimal point.
+    for (lenient, strict) in [
+      (".1_000", "0.1_000"),
+      (".5e3", "0.5e3"),
+      (".2_5E-1", "0.2_5E-1")
+    ] {
+      let source = format!("{lenient} == {strict}");
+      let tokens = Lexer::with_options(&source, options.clone()).lex().unwrap();
+
+      assert_eq!(tokens.len(), 4, "source : {source}");
+      assert_eq!(
+        *tokens[0].r#type(),
+        *tokens[2].r#type(),
+        "source : {source}"
+      );
+      assert_eq!(tokens[0].span().slice(&source), lenient);
+    }
+
+    let errors = Lexer::with_options(".1_", options).lex().unwrap_err();
+    assert_eq!(*errors[0].r#type(), ErrorType::InvalidDigitSeparator);
+  }
+
+  #[test]
+  fn leading_underscores() {
+    let tokens = Lexer::new("_ _foo __double _1st var foo_bar_ = _if;")
+      .lex()
+      .unwrap();
+
+    assert_eq!(
+      tokens.iter().map(Token::r#type).collect::<Vec<_>>(),
+      [
+        &TokenType::Identifier("_"),
+        &TokenType::Identifier("_foo"),
+        &TokenType::Identifier("__double"),
+        // Not a number, since it isn't made of digits only.
+        &TokenType::Identifier("_1st"),
+        &TokenType::Keyword(Keyword::Var),
+        &TokenType::Identifier("foo_bar_"),
+        &TokenType::Assign,
+        // Not the if keyword.
+        &TokenType::Identifier("_if"),
+        &TokenType::Semicolon,
+        &TokenType::Eof
+      ]
+    );
+  }
+
+  #[test]
+  fn custom_identifier_characters() {
+    let options = LexerOptions {
+      is_identifier_continue: |character| character.is_alphanumeric() || (character == '?'),
+      ..Default::default()
+    };
+
+    let tokens = Lexer::with_options("empty? list", options).lex().unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::Identifier("empty?"));
+    assert_eq!(*tokens[1].r#type(), TokenType::Identifier("list"));
+
+    // By default, the ? is an invalid character.
+    let errors = Lexer::new("empty?").lex().unwrap_err();
+    assert_eq!(errors[0].r#type, ErrorType::InvalidCharacter);
+  }
+
+  #[test]
+  fn scientific_notation() {
+    let tokens = Lexer::new("1e10 2.5e-3 6E+23").lex().unwrap();
+
+    let numbers = tokens
+      .iter()
+      .map(|token| (token.span().slice("1e10 2.5e-3 6E+23"), token.r#type()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      numbers[..3],
+      [
+        ("1e10", &TokenType::Number(1e10.into())),
+        ("2.5e-3", &TokenType::Number(2.5e-3.into())),
+        ("6E+23", &TokenType::Number(6e23.into()))
+      ]
+    );
+  }
+
+  #[test]
+  fn missing_exponent() {
+    // Along with the index of the exponent marker.
+    let cases = [("1e", 1), ("1e+ 2", 1), ("2.5E-", 3), ("1else", 1)];
+
+    for (source, marker) in cases {
+      let mut lexer = Lexer::new(source);
+
+      let error = lexer.next().unwrap().unwrap_err();
+      assert_eq!(
+        error.r#type,
+        ErrorType::MissingExponent,
+        "source : {source}"
+      );
+      assert_eq!(*error.position().index(), marker, "source : {source}");
+
+      // Nothing identifier like is left behind.
+      assert!(!matches!(
+        lexer.next().unwrap().unwrap().r#type(),
+        TokenType::Identifier(_) | TokenType::Keyword(_)
+      ));
+    }
+  }
+
+  #[test]
+  fn exponent_out_of_range() {
+    // Overflows to infinity.
+    let errors = Lexer::new("1e400").lex().unwrap_err();
+    assert_eq!(errors[0].r#type, ErrorType::FailedParsingNumber);
+
+    // Underflows to 0.
+    let tokens = Lexer::new("1e-400").lex().unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::Number(0.0.into()));
+  }
+
+  #[test]
+  fn digit_separators() {
+    let source = "1_000_000 0.123_456 1_0.5e1_0";
+
+    let tokens = Lexer::new(source).lex().unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::Number(1_000_000.0.into()));
+    assert_eq!(*tokens[1].r#type(), TokenType::Number(0.123_456.into()));
+    assert_eq!(*tokens[2].r#type(), TokenType::Number(10.5e10.into()));
+
+    // The spans still include the separators.
+    assert_eq!(tokens[0].span().slice(source), "1_000_000");
+  }
+
+  #[test]
+  fn invalid_digit_separators() {
+    // Along with the index of the misplaced underscore.
+    let cases = [
+      ("_1", 0),
+      ("_1_000", 0),
+      ("1_", 1),
+      ("1__0", 1),
+      ("1_.5", 1),
+      ("1._5", 2),
+      ("1.5_", 3),
+      ("1e5_", 3)
+    ];
+
+    for (source, underscore) in cases {
+      let errors = Lexer::new(source).lex().unwrap_err();
+
+      // The rest of the malformed number doesn't cause cascaded errors.
+      assert_eq!(errors.len(), 1, "source : {source}");
+      assert_eq!(
+        errors[0].r#type,
+        ErrorType::InvalidDigitSeparator,
+        "source : {source}"
+      );
+      assert_eq!(
+        *errors[0].position().index(),
+        underscore,
+        "source : {source}"
+      );
+    }
+  }
+
+  #[test]
+  fn hexadecimal_numbers() {
+    let source = "0xFF 0x0 0X1f";
+
+    let tokens = Lexer::new(source).lex().unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::Number(255.0.into()));
+    assert_eq!(*tokens[1].r#type(), TokenType::Number(0.0.into()));
+    assert_eq!(*tokens[2].r#type(), TokenType::Number(31.0.into()));
+    assert_eq!(tokens[2].span().slice(source), "0X1f");
+  }
+
+  #[test]
+  fn malformed_hexadecimal_numbers() {
+    let errors = Lexer::new("1 + 0x").lex().unwrap_err();
+    assert_eq!(errors[0].r#type, ErrorType::MissingHexadecimalDigits);
+    assert_eq!(*errors[0].position().index(), 4);
+
+    // Doesn't fit in an i64.
+    let errors = Lexer::new("0x10000000000000000").lex().unwrap_err();
+    assert_eq!(errors[0].r#type, ErrorType::FailedParsingNumber);
+  }
+
+  #[test]
+  fn custom_line_comment_marker() {
+    let options = LexerOptions {
+      line_comment: "#",
+      ..Default::default()
+    };
+
+    let tokens = Lexer::with_options("1 # note\n// 2", options)
+      .lex()
+      .unwrap();
+
+    let token_types = tokens
+      .iter()
+      .map(|token| token.r#type())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      token_types,
+      [
+        &TokenType::Number(1.0.into()),
+        // // is no longer a comment.
+        &TokenType::Divide,
+        &TokenType::Divide,
+        &TokenType::Number(2.0.into()),
+        &TokenType::Eof
+      ]
+    );
+  }
+
+  #[test]
+  fn strict_decimals() {
+    let errors = Lexer::new("5.").lex().unwrap_err();
+    assert_eq!(errors[0].r#type, ErrorType::NumberHasNoFractionalPart);
+
+    // A leading decimal point is a dot, which isn't a valid start of an expression.
+    let tokens = Lexer::new(".5").lex().unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::Dot);
+    assert!(Parser::new(tokens).parse().is_err());
+  }
+
+  #[test]
+  fn non_ascii_identifiers() {
+    let tokens = Lexer::new("café x٣").lex().unwrap();
+
+    assert_eq!(*tokens[0].r#type(), TokenType::Identifier("café"));
+    assert_eq!(*tokens[1].r#type(), TokenType::Identifier("x٣"));
+  }
+
   #[test]
   fn empty_string() {
     let source = "\"\"";
@@ -286,7 +1434,345 @@ mod tests {
     let tokens = lexer.lex().unwrap();
 
     let token = &tokens[0];
-    assert_eq!(*token.r#type(), TokenType::String(""));
+    assert_eq!(*token.r#type(), TokenType::String("".into()));
+  }
+
+  #[test]
+  fn escape_sequences() {
+    let tokens = Lexer::new(r#""say \"hi\"\n\ttab\r\0 \\ end""#)
+      .lex()
+      .unwrap();
+    assert_eq!(
+      *tokens[0].r#type(),
+      TokenType::String("say \"hi\"\n\ttab\r\0 \\ end".into())
+    );
+
+    // Strings without escape sequences borrow from the source.
+    let tokens = Lexer::new("\"plain\"").lex().unwrap();
+    assert!(matches!(
+      tokens[0].r#type(),
+      TokenType::String(Cow::Borrowed("plain"))
+    ));
+  }
+
+  #[test]
+  fn each_escape_sequence() {
+    let cases = [
+      (r#""\n""#, "\n"),
+      (r#""\t""#, "\t"),
+      (r#""\r""#, "\r"),
+      (r#""\0""#, "\0"),
+      (r#""\"""#, "\""),
+      (r#""\\""#, "\\")
+    ];
+
+    for (source, expected) in cases {
+      let tokens = Lexer::new(source).lex().unwrap();
+      assert_eq!(
+        *tokens[0].r#type(),
+        TokenType::String(expected.into()),
+        "source : {source}"
+      );
+    }
+  }
+
+  #[test]
+  fn backslash_before_closing_quote() {
+    // An escaped backslash, right before the closing double quote.
+    let tokens = Lexer::new(r#""dir\\" 1"#).lex().unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::String("dir\\".into()));
+    assert_eq!(*tokens[1].r#type(), TokenType::Number(1.0.into()));
+
+    // Otherwise, the backslash escapes the closing double quote.
+    let errors = Lexer::new(r#""dir\""#).lex().unwrap_err();
+    assert_eq!(errors[0].r#type, ErrorType::UnterminatedString);
+    assert_eq!(*errors[0].position().index(), 0);
+  }
+
+  #[test]
+  fn invalid_escape_sequence() {
+    let mut lexer = Lexer::new(r#""a\qb" 1"#);
+
+    let error = lexer.next().unwrap().unwrap_err();
+    assert_eq!(error.r#type, ErrorType::InvalidEscapeSequence);
+    // Points at the backslash.
+    assert_eq!(*error.position().index(), 2);
+
+    // Lexing resumes after the string.
+    let token = lexer.next().unwrap().unwrap();
+    assert_eq!(*token.r#type(), TokenType::Number(1.0.into()));
+  }
+
+  #[test]
+  fn block_comments() {
+    let tokens = Lexer::new("1 /* one * two / three */ + 2").lex().unwrap();
+    assert_eq!(tokens.len(), 4);
+    assert_eq!(*tokens[1].r#type(), TokenType::Plus);
+
+    // Line counts keep up with the newlines inside a block comment.
+    let tokens = Lexer::new("/*\n  first\n  second\n*/ x").lex().unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[0].position().to_string(), "line 4, column 4");
+  }
+
+  #[test]
+  fn nested_block_comments() {
+    let tokens = Lexer::new("1 /* outer /* inner */ still commented */ + 2")
+      .lex()
+      .unwrap();
+    assert_eq!(tokens.len(), 4);
+
+    let tokens = Lexer::new("/* one /* two /* three */\n two */ one */ x")
+      .lex()
+      .unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[0].position().to_string(), "line 2, column 16");
+  }
+
+  #[test]
+  fn unterminated_block_comment() {
+    let errors = Lexer::new("1 + /* never\nends *").lex().unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].r#type, ErrorType::UnterminatedBlockComment);
+    assert_eq!(errors[0].position().to_string(), "line 1, column 5");
+
+    // The inner comment is closed, but the outer one isn't.
+    let errors = Lexer::new("x /* outer /* inner */\n").lex().unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].r#type, ErrorType::UnterminatedBlockComment);
+    assert_eq!(errors[0].position().to_string(), "line 1, column 3");
+  }
+
+  #[test]
+  fn multi_line_string() {
+    let tokens = Lexer::new("\"abc\ndef\" x").lex().unwrap();
+
+    assert_eq!(*tokens[0].r#type(), TokenType::String("abc\ndef".into()));
+
+    // The newline inside the string advances the line counter.
+    assert_eq!(tokens[1].position().to_string(), "line 2, column 6");
+  }
+
+  #[test]
+  fn render_error() {
+    let source = "var a = 1;\nprint a ^ 2;";
+
+    let errors = Lexer::new(source).lex().unwrap_err();
+    assert_eq!(
+      errors[0].render(source),
+      "error: invalid character\n  --> line 2, column 9\n   | print a ^ 2;\n   |         ^\n"
+    );
+  }
+
+  #[test]
+  fn render_named_error() {
+    let source = "1\n2\nprint 1 + 2; \"oops";
+
+    let mut lexer = Lexer::new(source).with_name("path/to/file.lox");
+    let errors = lexer.lex().unwrap_err();
+
+    assert_eq!(
+      errors[0].render_named(source, lexer.name()),
+      "error: unterminated string\n  --> path/to/file.lox:3:14\n   | print 1 + 2; \"oops\n   |              ^\n"
+    );
+  }
+
+  #[test]
+  fn display_error() {
+    let errors = Lexer::new("1 +\n  ^").lex().unwrap_err();
+
+    assert_eq!(
+      errors[0].to_string(),
+      "lexer error at line 2, column 3: invalid character"
+    );
+
+    let errors = Lexer::new("1\n2\nprint 1 + 2; \"oops").lex().unwrap_err();
+
+    assert_eq!(
+      errors[0].to_string(),
+      "lexer error at line 3, column 14: unterminated string"
+    );
+  }
+
+  #[test]
+  fn shebang() {
+    let source = "#!/usr/bin/env lox (\nprint 1;";
+
+    let tokens = Lexer::new(source).lex().unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::Keyword(Keyword::Print));
+    assert_eq!(tokens[0].position().to_string(), "line 2, column 1");
+
+    assert!(matches!(
+      Lexer::delimiter_balance(source),
+      DelimiterBalance::Balanced
+    ));
+  }
+
+  #[test]
+  fn not_keyword() {
+    let tokens = Lexer::new("not nothing").lex().unwrap();
+
+    assert_eq!(*tokens[0].r#type(), TokenType::Keyword(Keyword::Not));
+    // Only the whole word is a keyword.
+    assert_eq!(*tokens[1].r#type(), TokenType::Identifier("nothing"));
+  }
+
+  #[test]
+  fn shebang_not_on_first_line() {
+    let errors = Lexer::new("print 1;\n#!/usr/bin/env lox")
+      .lex()
+      .unwrap_err();
+    assert_eq!(errors[0].r#type, ErrorType::InvalidCharacter);
+    assert_eq!(errors[0].position().to_string(), "line 2, column 1");
+
+    // Not even after leading whitespace.
+    let errors = Lexer::new(" #!/usr/bin/env lox").lex().unwrap_err();
+    assert_eq!(errors[0].r#type, ErrorType::InvalidCharacter);
+  }
+
+  #[test]
+  fn crlf_line_endings() {
+    let source = "var a = \"x\r\ny\";\r\n\r\nprint a;\n  a\r b";
+
+    let tokens = Lexer::new(source).lex().unwrap();
+
+    assert_eq!(*tokens[3].r#type(), TokenType::String("x\ny".into()));
+
+    let positions = tokens[4..9]
+      .iter()
+      .map(|token| token.position().to_string())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      positions,
+      [
+        "line 2, column 3",
+        "line 4, column 1",
+        "line 4, column 7",
+        "line 4, column 8",
+        "line 5, column 3"
+      ]
+    );
+    assert_eq!(tokens[9].position().to_string(), "line 6, column 2");
+
+    // The byte index advances by the real number of bytes.
+    assert_eq!(tokens[9].span().slice(source), "b");
+  }
+
+  #[test]
+  fn tab_width() {
+    let options = LexerOptions {
+      tab_width: 4,
+      ..Default::default()
+    };
+
+    let source = "\tprint\n  \tx\t1";
+    let tokens = Lexer::with_options(source, options).lex().unwrap();
+
+    let columns = tokens[..3]
+      .iter()
+      .map(|token| token.position().to_string())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      columns,
+      ["line 1, column 5", "line 2, column 5", "line 2, column 9"]
+    );
+
+    // A line index with the same tab width agrees with the tokens, both ways.
+    let line_index = LineIndex::new(source).with_tab_width(4);
+    for token in &tokens {
+      let position = line_index.position(*token.span().start());
+      assert_eq!(position, *token.position());
+      assert_eq!(line_index.offset(&position), *token.span().start());
+    }
+  }
+
+  #[test]
+  fn one_based_positions() {
+    let source = "var a = 1;\n  print a;\n\n\"b\"";
+
+    let tokens = Lexer::new(source).lex().unwrap();
+
+    let positions = tokens
+      .iter()
+      .map(|token| token.position().to_line_col())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      positions,
+      [
+        (1, 1),
+        (1, 5),
+        (1, 7),
+        (1, 9),
+        (1, 10),
+        (2, 3),
+        (2, 9),
+        (2, 10),
+        (4, 1),
+        // EOF
+        (4, 4)
+      ]
+    );
+
+    let position = tokens[6].position();
+    assert_eq!((position.line(), position.column()), (2, 9));
+    assert_eq!(position.to_string(), "line 2, column 9");
+  }
+
+  #[test]
+  fn trivia_tokens() {
+    let options = LexerOptions {
+      keep_trivia: true,
+      ..Default::default()
+    };
+
+    let tokens = Lexer::with_options("a /* b */ // c\r\n", options)
+      .lex()
+      .unwrap();
+
+    let token_types = tokens
+      .iter()
+      .map(|token| token.r#type())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      token_types,
+      [
+        &TokenType::Identifier("a"),
+        &TokenType::Whitespace(" "),
+        &TokenType::BlockComment("/* b */"),
+        &TokenType::Whitespace(" "),
+        &TokenType::LineComment("// c"),
+        &TokenType::Whitespace("\r\n"),
+        &TokenType::Eof
+      ]
+    );
+    assert_eq!(tokens[4].position().to_string(), "line 1, column 11");
+  }
+
+  #[test]
+  fn trivia_round_trip() {
+    let source =
+      "#!/usr/bin/env lox\nvar café = \"a\\n\"; // ünïcödé\r\n\t/* x /* y */ */\nprint café;  ";
+
+    let options = LexerOptions {
+      keep_trivia: true,
+      ..Default::default()
+    };
+    let tokens = Lexer::with_options(source, options).lex().unwrap();
+
+    let lexemes = tokens
+      .iter()
+      .map(|token| token.span().slice(source))
+      .collect::<String>();
+    assert_eq!(lexemes, source);
+
+    // Trivia isn't emitted by default.
+    let tokens = Lexer::new(source).lex().unwrap();
+    assert!(!tokens.iter().any(|token| matches!(
+      token.r#type(),
+      TokenType::Whitespace(_) | TokenType::LineComment(_) | TokenType::BlockComment(_)
+    )));
   }
 
   #[test]
@@ -304,4 +1790,301 @@ mod tests {
 
     assert!(lexer.lex().is_ok());
   }
+
+  #[test]
+  fn for_each_token_summary() {
+    let source = "var a = 1;\nvar b = ^;\nprint a + b # 2;";
+
+    let mut identifiers = Vec::new();
+    let mut error_types = Vec::new();
+
+    let summary = Lexer::new(source).for_each_token(
+      |token| {
+        if let TokenType::Identifier(identifier) = token.r#type() {
+          identifiers.push(*identifier);
+        }
+
+        ControlFlow::Continue(())
+      },
+      |error| error_types.push(error.r#type().to_string())
+    );
+
+    assert_eq!(identifiers, ["a", "b", "a", "b"]);
+    assert_eq!(error_types, ["invalid character", "invalid character"]);
+
+    // Excluding the EOF token.
+    assert_eq!(*summary.tokens(), 15);
+    assert_eq!(*summary.errors(), 2);
+    assert_eq!(*summary.lines(), 3);
+    assert_eq!(*summary.bytes(), source.len());
+  }
+
+  #[test]
+  fn for_each_token_stops_early() {
+    let source = "var a = 1;\nvar b = 2;";
+
+    let mut seen = 0;
+    let summary = Lexer::new(source).for_each_token(
+      |_| {
+        seen += 1;
+
+        match seen {
+          3 => ControlFlow::Break(()),
+          _ => ControlFlow::Continue(())
+        }
+      },
+      |_| {}
+    );
+
+    assert_eq!(seen, 3);
+    assert_eq!(*summary.tokens(), 3);
+    assert_eq!(*summary.lines(), 1);
+    assert_eq!(*summary.bytes(), "var a =".len());
+  }
+
+  #[test]
+  fn for_each_token_empty_source() {
+    let mut seen_eof = false;
+    let summary = Lexer::new("").for_each_token(
+      |token| {
+        seen_eof = *token.r#type() == TokenType::Eof;
+        ControlFlow::Continue(())
+      },
+      |_| {}
+    );
+
+    // The callback still gets the EOF token.
+    assert!(seen_eof);
+    assert_eq!(*summary.tokens(), 0);
+    assert_eq!(*summary.errors(), 0);
+  }
+
+  #[test]
+  fn balanced_delimiters() {
+    let balance = Lexer::delimiter_balance("fun f(a) { return [a, (a)]; }");
+    assert!(matches!(balance, DelimiterBalance::Balanced));
+  }
+
+  #[test]
+  fn incomplete_delimiters() {
+    let balance = Lexer::delimiter_balance("fun f(a) {\n  print (a");
+
+    let DelimiterBalance::Incomplete { unclosed } = balance
+    else {
+      panic!("expected incomplete input, got {balance:?}");
+    };
+    assert_eq!(unclosed, ['{', '(']);
+
+    let balance = Lexer::delimiter_balance("print \"abc");
+
+    let DelimiterBalance::Incomplete { unclosed } = balance
+    else {
+      panic!("expected incomplete input, got {balance:?}");
+    };
+    assert_eq!(unclosed, ['"']);
+  }
+
+  #[test]
+  fn mismatched_delimiters() {
+    let balance = Lexer::delimiter_balance("{ (1 + 2} )");
+
+    let DelimiterBalance::Mismatched {
+      position,
+      found,
+      expected
+    } = balance
+    else {
+      panic!("expected mismatched delimiters, got {balance:?}");
+    };
+    assert_eq!(found, '}');
+    assert_eq!(expected, Some(')'));
+    assert_eq!(*position.index(), 8);
+
+    let balance = Lexer::delimiter_balance("1)");
+    assert!(matches!(
+      balance,
+      DelimiterBalance::Mismatched {
+        found: ')',
+        expected: None,
+        ..
+      }
+    ));
+  }
+
+  #[test]
+  fn delimiters_inside_strings_and_comments() {
+    let balance = Lexer::delimiter_balance("print \"(}[\"; // {{\nprint 1;");
+    assert!(matches!(balance, DelimiterBalance::Balanced));
+
+    let balance = Lexer::delimiter_balance("f(\")\"");
+    assert!(matches!(balance, DelimiterBalance::Incomplete { .. }));
+
+    let balance = Lexer::delimiter_balance(r#"f("\")")"#);
+    assert!(matches!(balance, DelimiterBalance::Balanced));
+
+    let balance = Lexer::delimiter_balance("f(/* ) */ 1)");
+    assert!(matches!(balance, DelimiterBalance::Balanced));
+
+    let balance = Lexer::delimiter_balance("f(/* /* */ ) */ 1)");
+    assert!(matches!(balance, DelimiterBalance::Balanced));
+
+    let balance = Lexer::delimiter_balance("f( /* )");
+    assert!(matches!(
+      balance,
+      DelimiterBalance::Incomplete { unclosed } if unclosed == ['(', '*']
+    ));
+  }
+
+  #[test]
+  fn multi_byte_characters() {
+    let source = "// ünïcödé 🎉\nvar naïve = \"🎉 héllo\"; ñ";
+
+    let tokens = Lexer::new(source).lex().unwrap();
+
+    assert_eq!(*tokens[1].r#type(), TokenType::Identifier("naïve"));
+    assert_eq!(*tokens[3].r#type(), TokenType::String("🎉 héllo".into()));
+    assert_eq!(*tokens[5].r#type(), TokenType::Identifier("ñ"));
+
+    // Columns keep counting characters, while indices count bytes.
+    let position = tokens[5].position();
+    assert_eq!(
+      (position.line(), position.to_string()),
+      (2, "line 2, column 24".into())
+    );
+    assert_eq!(*position.index(), source.len() - "ñ".len());
+  }
+
+  #[test]
+  fn token_span_end_positions() {
+    let source = "a >= 12.75 while \"two\nlines\"";
+
+    let tokens = Lexer::new(source).lex().unwrap();
+    let line_index = LineIndex::new(source);
+
+    let ranges = tokens
+      .iter()
+      .map(|token| {
+        let (start, end) = line_index.positions(token.span());
+        (start.to_string(), end.to_string())
+      })
+      .collect::<Vec<_>>();
+
+    let range = |start: &str, end: &str| (start.to_string(), end.to_string());
+    assert_eq!(
+      ranges[1..5],
+      [
+        range("line 1, column 3", "line 1, column 5"),
+        range("line 1, column 6", "line 1, column 11"),
+        range("line 1, column 12", "line 1, column 17"),
+        // Including the quotes.
+        range("line 1, column 18", "line 2, column 7")
+      ]
+    );
+  }
+
+  #[test]
+  fn token_spans_slice_back_to_lexemes() {
+    let source = "var café = \"héllo 🎉\";\nprint café >= 1.5\n!= (x); // ünïcödé";
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.lex().unwrap();
+
+    let lexemes = tokens
+      .iter()
+      .map(|token| token.span().slice(source))
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      lexemes,
+      [
+        "var",
+        "café",
+        "=",
+        "\"héllo 🎉\"",
+        ";",
+        "print",
+        "café",
+        ">=",
+        "1.5",
+        "!=",
+        "(",
+        "x",
+        ")",
+        ";",
+        // The EOF token.
+        ""
+      ]
+    );
+
+    for token in &tokens {
+      assert_eq!(*token.span().start() as usize, *token.position().index());
+    }
+  }
+
+  #[test]
+  fn lex_with_recovery() {
+    let (tokens, errors) = Lexer::new("1 @ x # 2 + \"open").lex_with_recovery();
+
+    assert_eq!(
+      tokens.iter().map(Token::r#type).collect::<Vec<_>>(),
+      [
+        &TokenType::Number(1.0.into()),
+        &TokenType::Identifier("x"),
+        &TokenType::Number(2.0.into()),
+        &TokenType::Plus,
+        &TokenType::Eof
+      ]
+    );
+
+    assert_eq!(
+      errors
+        .iter()
+        .map(|error| (error.r#type.clone(), *error.position().index()))
+        .collect::<Vec<_>>(),
+      [
+        (ErrorType::InvalidCharacter, 2),
+        (ErrorType::InvalidCharacter, 6),
+        (ErrorType::UnterminatedString, 12)
+      ]
+    );
+  }
+
+  #[test]
+  fn interned_identifiers() {
+    let source = (0..5_000)
+      .map(|index| ["count", "total", "index"][index % 3])
+      .join(" + ");
+
+    let mut lexer = Lexer::new(&source).with_interner(Interner::default());
+    let tokens = lexer.lex().unwrap();
+    let interner = lexer.into_interner().unwrap();
+
+    let symbols = tokens
+      .iter()
+      .filter_map(|token| match token.r#type() {
+        TokenType::InternedIdentifier(symbol) => Some(*symbol),
+        _ => None
+      })
+      .collect::<Vec<_>>();
+
+    assert_eq!(symbols.len(), 5_000);
+    assert_eq!(interner.len(), 3);
+
+    // The same name always maps to the same symbol.
+    assert!(
+      symbols
+        .iter()
+        .step_by(3)
+        .all(|symbol| *symbol == symbols[0])
+    );
+    assert_eq!(interner.resolve(symbols[0]), "count");
+    assert_eq!(interner.resolve(symbols[1]), "total");
+
+    // Keywords aren't interned.
+    let tokens = Lexer::new("nil")
+      .with_interner(Interner::default())
+      .lex()
+      .unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::Keyword(Keyword::Nil));
+  }
 }
@@ -0,0 +1,305 @@
+use {
+  crate::lexer::{
+    Error, ErrorType, Lexer, LexerOptions,
+    interner::Interner,
+    source::{Position, Span},
+    token::{Token, TokenType}
+  },
+  std::io::Read
+};
+
+// Number of bytes read at a time.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+// Number of bytes read, when a lexeme runs past the end of the buffer for the first time. It
+// doubles each time the lexeme still doesn't fit, so that a long lexeme (like a huge string) gets
+// re-lexed only a logarithmic number of times.
+const INITIAL_READ_SIZE: usize = CHUNK_SIZE;
+
+// Lexes a source as it's being read, like when Lox source gets piped in through stdin. Only the
+// part of the source, which the current lexeme spans, is kept buffered.
+//
+// Since the source doesn't stay around, the tokens are owned. Identifiers get interned into the
+// lexer's interner, and are emitted as TokenType::InternedIdentifier.
+pub struct ReaderLexer<R: Read> {
+  reader: R,
+
+  // Trivia is never kept, since it can't be owned.
+  options: LexerOptions,
+
+  // What's been read, but not yet lexed.
+  buffer: String,
+
+  // Trailing bytes of a character, which has only been read partially.
+  incomplete_character: Vec<u8>,
+
+  // Position in the whole source, that the buffer starts at.
+  start: Position,
+
+  interner: Interner,
+
+  // Whether everything has been read.
+  reached_end_of_reader: bool,
+
+  // Whether the EOF token has been emitted, or reading failed.
+  finished: bool
+}
+
+impl Lexer<'_> {
+  pub fn from_reader<R: Read>(reader: R) -> ReaderLexer<R> {
+    Self::from_reader_with_options(reader, LexerOptions::default())
+  }
+
+  pub fn from_reader_with_options<R: Read>(reader: R, options: LexerOptions) -> ReaderLexer<R> {
+    ReaderLexer {
+      reader,
+      options: LexerOptions {
+        keep_trivia: false,
+        ..options
+      },
+      buffer: String::new(),
+      incomplete_character: Vec::new(),
+      start: Position::default(),
+      interner: Interner::default(),
+      reached_end_of_reader: false,
+      finished: false
+    }
+  }
+}
+
+impl<R: Read> ReaderLexer<R> {
+  // Resolves the symbols of the interned identifiers.
+  pub fn interner(&self) -> &Interner {
+    &self.interner
+  }
+
+  pub fn into_interner(self) -> Interner {
+    self.interner
+  }
+
+  // Appends at least the given number of bytes of the source to the buffer, unless the source
+  // ends before that.
+  fn read(&mut self, size: usize) -> Result<(), Error> {
+    let mut read = 0;
+    while (read < size) && !self.reached_end_of_reader {
+      read += self.read_chunk()?;
+    }
+    Ok(())
+  }
+
+  // Appends the next chunk of the source to the buffer, returning the number of bytes read.
+  fn read_chunk(&mut self) -> Result<usize, Error> {
+    let failed_reading_source = || Error::new(self.start, ErrorType::FailedReadingSource);
+
+    let mut chunk = [0; CHUNK_SIZE];
+    let length = self
+      .reader
+      .read(&mut chunk)
+      .map_err(|_| failed_reading_source())?;
+
+    if length == 0 {
+      self.reached_end_of_reader = true;
+
+      // The source ends in the middle of a character.
+      if !self.incomplete_character.is_empty() {
+        return Err(failed_reading_source());
+      }
+      return Ok(0);
+    }
+
+    let mut bytes = std::mem::take(&mut self.incomplete_character);
+    bytes.extend_from_slice(&chunk[..length]);
+
+    match std::str::from_utf8(&bytes) {
+      Ok(string) => self.buffer.push_str(string),
+
+      // The chunk ends in the middle of a character, the rest of which is yet to be read.
+      Err(error) if error.error_len().is_none() => {
+        let (valid, incomplete) = bytes.split_at(error.valid_up_to());
+
+        self
+          .buffer
+          .push_str(std::str::from_utf8(valid).expect("validated above"));
+        self.incomplete_character = incomplete.to_vec();
+      }
+
+      Err(_) => return Err(failed_reading_source())
+    }
+
+    Ok(length)
+  }
+}
+
+impl<R: Read> Iterator for ReaderLexer<R> {
+  type Item = Result<Token<'static>, Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.finished {
+      return None;
+    }
+
+    let mut read_size = INITIAL_READ_SIZE;
+    loop {
+      let mut lexer =
+        Lexer::with_options(&self.buffer, self.options.clone()).starting_at(&self.start);
+
+      let result = lexer
+        .next()
+        .expect("the lexer always emits at least the EOF token");
+      let end = *lexer.position();
+
+      // The lexeme runs till the end of the buffer. So, it may continue in the part of the source
+      // which is yet to be read (like 12 followed by 3, or an unterminated string).
+      if (end.index() == &self.buffer.len()) && !self.reached_end_of_reader {
+        if let Err(error) = self.read(read_size) {
+          self.finished = true;
+          return Some(Err(error));
+        }
+        read_size *= 2;
+        continue;
+      }
+
+      let result = match result {
+        Ok(token) => Ok(
+          token
+            .into_owned(&mut self.interner, &self.start)
+            .expect("trivia isn't kept")
+        ),
+
+        Err(error) => {
+          let offset = *self.start.index() as u32;
+
+          Err(Error {
+            position: error.position.offset_by(&self.start),
+            r#type:   error.r#type,
+            span:     Span::new(error.span.start() + offset, error.span.end() + offset)
+          })
+        }
+      };
+
+      if matches!(&result, Ok(token) if token.r#type() == &TokenType::Eof) {
+        self.finished = true;
+      }
+
+      // Drop the lexed part of the buffer.
+      self.buffer.drain(..*end.index());
+      self.start = end.offset_by(&self.start);
+
+      return Some(result);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, std::io};
+
+  // Hands out a single byte at a time, so that every lexeme spans reads.
+  struct Trickle<'trickle>(&'trickle [u8]);
+
+  impl Read for Trickle<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+      let Some((first, rest)) = self.0.split_first()
+      else {
+        return Ok(0);
+      };
+
+      buffer[0] = *first;
+      self.0 = rest;
+      Ok(1)
+    }
+  }
+
+  #[test]
+  fn same_tokens_as_lexing_a_str() {
+    let source =
+      "var café = \"a\\n\" + 123.45;\r\n// ünïcödé\n/* x /* y */ */ print café >= 1_000 != nil;";
+
+    let mut reader_lexer = Lexer::from_reader(Trickle(source.as_bytes()));
+    let tokens = reader_lexer
+      .by_ref()
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+
+    let expected = Lexer::new(source)
+      .with_interner(Interner::default())
+      .lex()
+      .unwrap();
+
+    assert_eq!(tokens, expected);
+    assert_eq!(reader_lexer.interner().len(), 1);
+  }
+
+  #[test]
+  fn respects_the_options() {
+    let options = LexerOptions {
+      lenient_decimals: true,
+      tab_width: 4,
+      ..LexerOptions::default()
+    };
+
+    // The tabs are mid-line, after what's been lexed (and dropped from the buffer) already.
+    let source = "ab\t.5 +\tcd;\n\t5.";
+
+    let tokens = Lexer::from_reader_with_options(Trickle(source.as_bytes()), options.clone())
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+
+    let expected = Lexer::with_options(source, options)
+      .with_interner(Interner::default())
+      .lex()
+      .unwrap();
+
+    assert_eq!(tokens, expected);
+  }
+
+  #[test]
+  fn lexeme_longer_than_a_chunk() {
+    let string = "a".repeat(5 * CHUNK_SIZE);
+    let source = format!("\"{string}\" + 1");
+
+    let tokens = Lexer::from_reader(Trickle(source.as_bytes()))
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+
+    assert_eq!(*tokens[0].r#type(), TokenType::String(string.into()));
+    assert_eq!(*tokens[2].position().index(), source.len() - 1);
+  }
+
+  #[test]
+  fn unterminated_string() {
+    let source = "1 +\n  \"abc";
+
+    let results = Lexer::from_reader(Trickle(source.as_bytes())).collect::<Vec<_>>();
+    assert_eq!(results.len(), 4);
+
+    let error = results[2].as_ref().unwrap_err();
+    assert_eq!(error.r#type, ErrorType::UnterminatedString);
+    assert_eq!(error.position().to_line_col(), (2, 3));
+
+    let eof = results[3].as_ref().unwrap();
+    assert_eq!(*eof.r#type(), TokenType::Eof);
+    assert_eq!(*eof.position().index(), source.len());
+  }
+
+  #[test]
+  fn shebang_only_at_the_beginning() {
+    let tokens = Lexer::from_reader(Trickle(b"#!/usr/bin/env lox\n1"))
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+    assert_eq!(*tokens[0].r#type(), TokenType::Number(1.0.into()));
+
+    let errors = Lexer::from_reader(Trickle(b"1#!x"))
+      .filter_map(Result::err)
+      .collect::<Vec<_>>();
+    assert_eq!(errors[0].r#type, ErrorType::InvalidCharacter);
+  }
+
+  #[test]
+  fn invalid_utf8() {
+    let results = Lexer::from_reader(Trickle(b"1 + \xff")).collect::<Vec<_>>();
+
+    let error = results.last().unwrap().as_ref().unwrap_err();
+    assert_eq!(error.r#type, ErrorType::FailedReadingSource);
+  }
+}
@@ -1,4 +1,5 @@
 use {
+  derive_more::Constructor,
   getset::Getters,
   std::{fmt::Display, iter::Peekable, str::Chars}
 };
@@ -8,27 +9,73 @@ pub struct Source<'source> {
   #[getset(get = "pub")]
   source: &'source str,
 
+  // Like the path of the file the source was read from, if any. Reported along with positions.
+  #[getset(get = "pub")]
+  name: Option<&'source str>,
+
   characters: Peekable<Chars<'source>>,
 
   #[getset(get = "pub")]
-  position: Position
+  position: Position,
+
+  // Number of columns a tab advances to the next tab stop by.
+  tab_width: usize,
+
+  // Column of the whole source, that the first line starts at. Non-zero, when this is a piece of
+  // a larger source starting mid-line, so that tab stops on the first line still line up.
+  first_line_column: usize
 }
 
 impl<'source> Source<'source> {
   pub fn new(source: &'source str) -> Self {
     Self {
       source,
+      name: None,
       characters: source.chars().peekable(),
-      position: Position::default()
+      position: Position::default(),
+      tab_width: 1,
+      first_line_column: 0
+    }
+  }
+
+  pub fn with_tab_width(self, tab_width: usize) -> Self {
+    Self {
+      tab_width: tab_width.max(1),
+      ..self
+    }
+  }
+
+  // Treats this as a piece of a larger source, which starts at the given position. The positions
+  // stay relative to the piece.
+  pub(crate) fn starting_at(self, start: &Position) -> Self {
+    Self {
+      first_line_column: start.column,
+      ..self
+    }
+  }
+
+  pub fn with_name(self, name: &'source str) -> Self {
+    Self {
+      name: Some(name),
+      ..self
     }
   }
 
+  // Line terminators (\r\n, or a lone \r) are seen as a \n.
   pub fn peek(&mut self) -> Option<&char> {
-    self.characters.peek()
+    self
+      .characters
+      .peek()
+      .map(|character| if *character == '\r' { &'\n' } else { character })
+  }
+
+  // Peeks at the character after the next one.
+  pub fn peek_second(&self) -> Option<char> {
+    self.source[self.position.index..].chars().nth(1)
   }
 
   pub fn next_if(&mut self, predicate: impl FnOnce(&char) -> bool) -> Option<(Position, char)> {
-    let next_character = self.characters.peek()?;
+    let next_character = self.peek()?;
 
     let preicate_satisfied = predicate(next_character);
 
@@ -71,47 +118,518 @@ impl<'source> Iterator for Source<'source> {
   type Item = (Position, char);
 
   fn next(&mut self) -> Option<Self::Item> {
-    let position = self.position.clone();
+    let position = self.position;
     let character = self.characters.next()?;
 
     // Update the position tracker.
     match character {
-      '\n' => self.position.move_to_next_line(),
-      _ => self.position.move_to_next_column()
+      '\n' => self.position.move_to_next_line(1),
+
+      // A \r\n is a single line terminator, and so is a lone \r. Either way, it's yielded as a
+      // \n.
+      '\r' => {
+        let length = match self.characters.next_if_eq(&'\n') {
+          Some(_) => 2,
+          None => 1
+        };
+        self.position.move_to_next_line(length);
+
+        return Some((position, '\n'));
+      }
+
+      '\t' => {
+        let column_offset = if self.position.line == 0 { self.first_line_column } else { 0 };
+        self
+          .position
+          .move_to_next_tab_stop(self.tab_width, column_offset);
+      }
+
+      _ => self.position.move_to_next_column(character)
     }
 
     Some((position, character))
   }
 }
 
-#[derive(Debug, Default, Clone, Copy, Getters)]
+// Internally, lines and columns are zero-based. But they're exposed one-based, the way editors
+// report them.
+// When serialized, the line and column are the internal zero-based ones.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
-  line:   usize,
+  line: usize,
+
   column: usize,
 
+  // Byte offset into the source. The column, on the other hand, counts characters (with a tab
+  // counting till the next tab stop).
   #[getset(get = "pub")]
   index: usize
 }
 
 impl Position {
-  fn move_to_next_column(&mut self) {
+  pub fn line(&self) -> usize {
+    self.line + 1
+  }
+
+  pub fn column(&self) -> usize {
+    self.column + 1
+  }
+
+  // The one-based line and column.
+  pub fn to_line_col(&self) -> (usize, usize) {
+    (self.line(), self.column())
+  }
+
+  // Translates a position within a piece of the source, to one within the whole source, given
+  // the position the piece starts at.
+  pub(crate) fn offset_by(&self, start: &Position) -> Self {
+    Self {
+      line:   start.line + self.line,
+      column: if self.line == 0 { start.column + self.column } else { self.column },
+      index:  start.index + self.index
+    }
+  }
+
+  // Scans the source, till the given byte offset, to determine the line and column.
+  // Returns None, if the offset is out of bounds or doesn't lie on a character boundary.
+  pub fn from_byte_offset(source: &str, offset: usize) -> Option<Self> {
+    let preceding = source.get(..offset)?;
+
+    let (line, line_start) = line_containing(source, offset);
+
+    Some(Self {
+      line,
+      column: preceding[line_start..].chars().count(),
+      index: offset
+    })
+  }
+
+  // Editor protocols like LSP, count columns in UTF-16 code units, rather than characters.
+  pub fn utf16_column(&self, source: &str) -> usize {
+    let (_, line_start) = line_containing(source, self.index);
+
+    source[line_start..self.index].encode_utf16().count()
+  }
+
+  // Text of the line this position lies on, without the line terminator.
+  pub fn line_text<'source>(&self, source: &'source str) -> &'source str {
+    let (_, line_start) = line_containing(source, self.index);
+
+    source[line_start..]
+      .split(['\r', '\n'])
+      .next()
+      .unwrap_or_default()
+  }
+
+  // Renders the message, along with the line of source this position lies on and a caret pointing
+  // at the column, like :
+  //
+  //   error: unterminated string
+  //     --> line 1, column 5
+  //      | 1 + "abc
+  //      |     ^
+  pub fn render(&self, source: &str, message: &str) -> String {
+    self.render_named(source, None, message)
+  }
+
+  // Like render, but when the source has a name, the position is reported as name:line:column
+  // instead, like :
+  //
+  //     --> path/to/file.lox:1:5
+  pub fn render_named(&self, source: &str, name: Option<&str>, message: &str) -> String {
+    let location = match name {
+      Some(name) => format!("{name}:{}:{}", self.line(), self.column()),
+      None => self.to_string()
+    };
+
+    format!(
+      "error: {message}\n  --> {location}\n   | {}\n   | {}^\n",
+      self.line_text(source),
+      " ".repeat(self.column)
+    )
+  }
+
+  fn move_to_next_column(&mut self, character: char) {
     self.column += 1;
 
-    // Also, increment the index.
+    // Also, advance the index by the number of bytes the character occupies.
+    self.index += character.len_utf8();
+  }
+
+  // Tab stops are relative to the column the line is offset by.
+  fn move_to_next_tab_stop(&mut self, tab_width: usize, column_offset: usize) {
+    self.column = ((((self.column + column_offset) / tab_width) + 1) * tab_width) - column_offset;
     self.index += 1;
   }
 
-  fn move_to_next_line(&mut self) {
+  // The line terminator occupies the given number of bytes.
+  fn move_to_next_line(&mut self, length: usize) {
     self.line += 1;
     self.column = 0;
 
-    // Also, increment the index.
-    self.index += 1;
+    // Also, advance the index.
+    self.index += length;
   }
 }
 
 impl Display for Position {
   fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    write!(formatter, "line {}, column {}", self.line, self.column)
+    write!(formatter, "line {}, column {}", self.line(), self.column())
+  }
+}
+
+// A half-open range of byte offsets [start, end) into the source.
+// Unlike a Position, it's cheap to store on every node. The line and column get derived lazily,
+// using a LineIndex, only when we need to display them.
+#[derive(
+  Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Constructor, Getters,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+  #[getset(get = "pub")]
+  start: u32,
+
+  #[getset(get = "pub")]
+  end: u32
+}
+
+impl Span {
+  pub fn between(start: &Position, end: &Position) -> Self {
+    Self {
+      start: start.index as u32,
+      end:   end.index as u32
+    }
+  }
+
+  // Returns the smallest span covering both the spans.
+  pub fn merge(&self, other: &Self) -> Self {
+    Self {
+      start: self.start.min(other.start),
+      end:   self.end.max(other.end)
+    }
+  }
+
+  pub fn contains(&self, offset: u32) -> bool {
+    (self.start <= offset) && (offset < self.end)
+  }
+
+  pub fn slice<'source>(&self, source: &'source str) -> &'source str {
+    &source[(self.start as usize)..(self.end as usize)]
+  }
+}
+
+// Byte offsets at which the lines after the first one start. Lines end at the same terminators
+// Source recognizes : a \r\n, a lone \r or a \n.
+fn line_starts(source: &str) -> impl Iterator<Item = usize> + '_ {
+  let bytes = source.as_bytes();
+
+  bytes
+    .iter()
+    .enumerate()
+    .filter_map(move |(index, byte)| match byte {
+      b'\n' => Some(index + 1),
+
+      // The \r of a \r\n, which ends after the \n instead.
+      b'\r' if bytes.get(index + 1) == Some(&b'\n') => None,
+      b'\r' => Some(index + 1),
+
+      _ => None
+    })
+}
+
+// The zero-based line the byte offset lies on, along with the byte offset that line starts at.
+fn line_containing(source: &str, offset: usize) -> (usize, usize) {
+  line_starts(source)
+    .take_while(|line_start| *line_start <= offset)
+    .fold((0, 0), |(line, _), line_start| (line + 1, line_start))
+}
+
+// Stores the byte offset at which each line starts, so that we can map a byte offset back to a
+// Position using a binary search, instead of rescanning the whole source.
+pub struct LineIndex<'line_index> {
+  source:      &'line_index str,
+  line_starts: Vec<u32>,
+
+  // Should match the lexer's, for the positions to agree with those of the tokens.
+  tab_width: usize
+}
+
+impl<'line_index> LineIndex<'line_index> {
+  pub fn new(source: &'line_index str) -> Self {
+    let line_starts = std::iter::once(0)
+      .chain(line_starts(source).map(|line_start| line_start as u32))
+      .collect();
+
+    Self {
+      source,
+      line_starts,
+      tab_width: 1
+    }
+  }
+
+  pub fn with_tab_width(self, tab_width: usize) -> Self {
+    Self {
+      tab_width: tab_width.max(1),
+      ..self
+    }
+  }
+
+  pub fn position(&self, offset: u32) -> Position {
+    let line = self
+      .line_starts
+      .partition_point(|line_start| *line_start <= offset)
+      - 1;
+
+    let line_start = self.line_starts[line] as usize;
+    let column = self.source[line_start..(offset as usize)]
+      .chars()
+      .fold(0, |column, character| self.next_column(column, character));
+
+    Position {
+      line,
+      column,
+      index: offset as usize
+    }
+  }
+
+  pub fn offset(&self, position: &Position) -> u32 {
+    let line_start = self.line_starts[position.line] as usize;
+
+    let mut column = 0;
+    let column_width: usize = self.source[line_start..]
+      .chars()
+      .take_while(|character| {
+        let taken = column < position.column;
+        column = self.next_column(column, *character);
+
+        taken
+      })
+      .map(char::len_utf8)
+      .sum();
+
+    (line_start + column_width) as u32
+  }
+
+  pub fn positions(&self, span: &Span) -> (Position, Position) {
+    (self.position(span.start), self.position(span.end))
+  }
+
+  // The column following the character, the same way Source advances it.
+  fn next_column(&self, column: usize, character: char) -> usize {
+    match character {
+      '\t' => ((column / self.tab_width) + 1) * self.tab_width,
+      _ => column + 1
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn span_merging_across_lines() {
+    let source = "1 +\n  2";
+
+    let line_index = LineIndex::new(source);
+
+    let left = Span::new(0, 1);
+    let right = Span::new(6, 7);
+
+    let merged = left.merge(&right);
+    assert_eq!(merged, Span::new(0, 7));
+    assert_eq!(merged, right.merge(&left));
+    assert_eq!(merged.slice(source), source);
+
+    assert!(merged.contains(4));
+    assert!(!merged.contains(7));
+
+    let (start, end) = line_index.positions(&merged);
+    assert_eq!((start.line, start.column), (0, 0));
+    assert_eq!((end.line, end.column), (1, 3));
+  }
+
+  #[test]
+  fn conversion_round_trips_on_multi_byte_text() {
+    let source = "var café = \"héllo\";\n// ünïcödé 🎉\nprint café;";
+
+    let line_index = LineIndex::new(source);
+
+    for (offset, _) in source.char_indices() {
+      let position = line_index.position(offset as u32);
+      assert_eq!(*position.index(), offset);
+      assert_eq!(line_index.offset(&position), offset as u32);
+    }
+
+    let offset = source.find("print").unwrap() as u32;
+    let position = line_index.position(offset);
+    assert_eq!((position.line, position.column), (2, 0));
+
+    let offset = source.find('🎉').unwrap() as u32;
+    let position = line_index.position(offset);
+    assert_eq!((position.line, position.column), (1, 11));
+  }
+
+  #[test]
+  fn position_from_byte_offset() {
+    let source = "var é = 1;\nprint \"🎉\" + é;";
+
+    let offset = source.rfind('é').unwrap();
+    let position = Position::from_byte_offset(source, offset).unwrap();
+    assert_eq!((position.line, position.column), (1, 12));
+    assert_eq!(position.utf16_column(source), 13);
+
+    let offset = source.find('🎉').unwrap();
+    let position = Position::from_byte_offset(source, offset).unwrap();
+    assert_eq!((position.line, position.column), (1, 7));
+    assert_eq!(position.utf16_column(source), 7);
+
+    let offset = source.find('=').unwrap();
+    let position = Position::from_byte_offset(source, offset).unwrap();
+    assert_eq!((position.line, position.column), (0, 6));
+    assert_eq!(position.utf16_column(source), 6);
+
+    // The end of the source is a valid offset.
+    let position = Position::from_byte_offset(source, source.len()).unwrap();
+    assert_eq!((position.line, position.column), (1, 14));
+    assert_eq!(position.utf16_column(source), 15);
+
+    // In the middle of 🎉.
+    assert!(Position::from_byte_offset(source, source.find('🎉').unwrap() + 1).is_none());
+    assert!(Position::from_byte_offset(source, source.len() + 1).is_none());
+  }
+
+  #[test]
+  fn render_last_line_without_trailing_newline() {
+    let source = "1 +\n2 ^";
+    let position = Position::from_byte_offset(source, 6).unwrap();
+
+    assert_eq!(position.line_text(source), "2 ^");
+    assert_eq!(
+      position.render(source, "invalid character"),
+      "error: invalid character\n  --> line 2, column 3\n   | 2 ^\n   |   ^\n"
+    );
+  }
+
+  #[test]
+  fn render_named() {
+    let source = "1 +\n2 ^";
+    let position = Position::from_byte_offset(source, 6).unwrap();
+
+    assert_eq!(
+      position.render_named(source, Some("path/to/file.lox"), "invalid character"),
+      "error: invalid character\n  --> path/to/file.lox:2:3\n   | 2 ^\n   |   ^\n"
+    );
+    assert_eq!(
+      position.render_named(source, None, "invalid character"),
+      position.render(source, "invalid character")
+    );
+  }
+
+  #[test]
+  fn render_empty_line() {
+    let source = "1 +\n\n2";
+    let position = Position::from_byte_offset(source, 4).unwrap();
+
+    assert_eq!(position.line_text(source), "");
+    assert_eq!(
+      position.render(source, "expected a literal"),
+      "error: expected a literal\n  --> line 2, column 1\n   | \n   | ^\n"
+    );
+
+    // At the end of a source, which ends with a newline.
+    let position = Position::from_byte_offset("1\n", 2).unwrap();
+    assert_eq!(position.line_text("1\n"), "");
+  }
+
+  #[test]
+  fn line_terminators() {
+    let source = "a\r\nb\rc\nd";
+
+    let characters = Source::new(source)
+      .map(|(position, character)| (position.to_string(), position.index, character))
+      .collect::<Vec<_>>();
+
+    let item = |position: &str, index, character| (position.to_string(), index, character);
+    assert_eq!(
+      characters,
+      [
+        item("line 1, column 1", 0, 'a'),
+        item("line 1, column 2", 1, '\n'),
+        item("line 2, column 1", 3, 'b'),
+        item("line 2, column 2", 4, '\n'),
+        item("line 3, column 1", 5, 'c'),
+        item("line 3, column 2", 6, '\n'),
+        item("line 4, column 1", 7, 'd')
+      ]
+    );
+  }
+
+  #[test]
+  fn lone_carriage_return() {
+    let source = "1\r^";
+
+    let line_index = LineIndex::new(source);
+    let position = line_index.position(2);
+    assert_eq!(position.to_line_col(), (2, 1));
+    assert_eq!(line_index.offset(&position), 2);
+
+    let position = Position::from_byte_offset(source, 2).unwrap();
+    assert_eq!(position.to_line_col(), (2, 1));
+    assert_eq!(position.utf16_column(source), 0);
+
+    // Agrees with the position the Source tracks.
+    assert_eq!(Source::new(source).nth(2).unwrap().0, position);
+
+    assert_eq!(
+      position.render(source, "invalid character"),
+      "error: invalid character\n  --> line 2, column 1\n   | ^\n   | ^\n"
+    );
+
+    // The line before the \r doesn't include it.
+    let position = Position::from_byte_offset(source, 0).unwrap();
+    assert_eq!(position.line_text(source), "1");
+
+    // A \r\n is a single line terminator.
+    let source = "1\r\n\r\n^";
+    assert_eq!(LineIndex::new(source).position(5).to_line_col(), (3, 1));
+    assert_eq!(
+      Position::from_byte_offset(source, 5).unwrap().to_line_col(),
+      (3, 1)
+    );
+  }
+
+  #[test]
+  fn tab_stops() {
+    let positions = |tab_width| {
+      Source::new("\ta\tbc\td")
+        .with_tab_width(tab_width)
+        .map(|(position, _)| position.column)
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(positions(1), [0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(positions(4), [0, 4, 5, 8, 9, 10, 12]);
+  }
+
+  #[test]
+  fn source_tracks_byte_offsets() {
+    let source = "é🎉\nx";
+
+    let mut positions = Source::new(source).map(|(position, _)| position);
+
+    let (first, second, third, fourth) = (
+      positions.next().unwrap(),
+      positions.next().unwrap(),
+      positions.next().unwrap(),
+      positions.next().unwrap()
+    );
+
+    assert_eq!(
+      [first.index, second.index, third.index, fourth.index],
+      [0, 2, 6, 7]
+    );
+    assert_eq!((fourth.line, fourth.column), (1, 0));
   }
 }
@@ -1,109 +1,238 @@
 use {
-  crate::lexer::source::Position, derive_more::Constructor, getset::Getters,
-  ordered_float::OrderedFloat, strum::Display, strum_macros::EnumString
+  crate::{
+    ast::evaluator::value::Value,
+    lexer::{
+      interner::{Interner, Symbol},
+      source::{Position, Span}
+    }
+  },
+  derive_more::Constructor,
+  getset::Getters,
+  ordered_float::OrderedFloat,
+  std::{borrow::Cow, fmt::Display},
+  strum::IntoEnumIterator,
+  strum_macros::{EnumIter, EnumString}
 };
 
-#[derive(Debug, Constructor, Getters)]
+#[derive(Debug, PartialEq, Constructor, Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token<'token> {
   #[getset(get = "pub")]
+  #[cfg_attr(feature = "serde", serde(borrow))]
   r#type: TokenType<'token>,
 
+  // Kept around for displaying the token's location.
   #[getset(get = "pub")]
-  position: Position
+  position: Position,
+
+  #[getset(get = "pub")]
+  span: Span
 }
 
 impl<'token> Token<'token> {
+  // Detaches the token from the piece of source it was lexed from, which starts at the given
+  // position. Identifiers get interned, since they'd otherwise borrow from the source. Returns
+  // None for trivia, which can only borrow.
+  pub(crate) fn into_owned(
+    self,
+    interner: &mut Interner,
+    start: &Position
+  ) -> Option<Token<'static>> {
+    let r#type = match self.r#type {
+      TokenType::OpenParanthesis => TokenType::OpenParanthesis,
+      TokenType::CloseParanthesis => TokenType::CloseParanthesis,
+      TokenType::OpenBrace => TokenType::OpenBrace,
+      TokenType::CloseBrace => TokenType::CloseBrace,
+      TokenType::Comma => TokenType::Comma,
+      TokenType::Dot => TokenType::Dot,
+      TokenType::Semicolon => TokenType::Semicolon,
+      TokenType::Plus => TokenType::Plus,
+      TokenType::Minus => TokenType::Minus,
+      TokenType::Multiply => TokenType::Multiply,
+      TokenType::Divide => TokenType::Divide,
+      TokenType::Percent => TokenType::Percent,
+      TokenType::Assign => TokenType::Assign,
+      TokenType::Not => TokenType::Not,
+      TokenType::NotEquals => TokenType::NotEquals,
+      TokenType::Equals => TokenType::Equals,
+      TokenType::GreaterThan => TokenType::GreaterThan,
+      TokenType::GreaterThanOrEquals => TokenType::GreaterThanOrEquals,
+      TokenType::LessThan => TokenType::LessThan,
+      TokenType::LessThanOrEquals => TokenType::LessThanOrEquals,
+
+      TokenType::String(string) => TokenType::String(Cow::Owned(string.into_owned())),
+      TokenType::Number(number) => TokenType::Number(number),
+      TokenType::Identifier(identifier) =>
+        TokenType::InternedIdentifier(interner.intern(identifier)),
+      TokenType::InternedIdentifier(symbol) => TokenType::InternedIdentifier(symbol),
+      TokenType::Keyword(keyword) => TokenType::Keyword(keyword),
+
+      TokenType::Whitespace(_) | TokenType::LineComment(_) | TokenType::BlockComment(_) => {
+        return None;
+      }
+
+      TokenType::Eof => TokenType::Eof
+    };
+
+    let offset = *start.index() as u32;
+
+    Some(Token::new(
+      r#type,
+      self.position.offset_by(start),
+      Span::new(self.span.start() + offset, self.span.end() + offset)
+    ))
+  }
+
   pub fn is_literal(&self) -> bool {
     matches!(
       self.r#type(),
-      TokenType::Number(_) | TokenType::String(_) | TokenType::Identifier(_)
+      TokenType::Number(_)
+        | TokenType::String(_)
+        | TokenType::Identifier(_)
+        | TokenType::InternedIdentifier(_)
+        | TokenType::Keyword(Keyword::True | Keyword::False | Keyword::Nil)
     )
   }
 }
 
-#[derive(Debug, PartialEq, Eq, Display)]
+// When serialized, numbers are plain f64s, and the string payloads borrow from the input
+// wherever possible.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenType<'token_type> {
-  #[strum(to_string = "(")]
   OpenParanthesis,
 
-  #[strum(to_string = ")")]
   CloseParanthesis,
 
-  #[strum(to_string = "{")]
   OpenBrace,
 
-  #[strum(to_string = "}}")]
   CloseBrace,
 
-  #[strum(to_string = ",")]
   Comma,
 
-  #[strum(to_string = ".")]
   Dot,
 
-  #[strum(to_string = ";")]
   Semicolon,
 
-  #[strum(to_string = "+")]
   Plus,
 
-  #[strum(to_string = "-")]
   Minus,
 
-  #[strum(to_string = "*")]
   Multiply,
 
-  #[strum(to_string = "/")]
   Divide,
 
-  #[strum(to_string = "=")]
+  Percent,
+
   Assign,
 
-  #[strum(to_string = "!")]
   Not,
 
-  #[strum(to_string = "!=")]
   NotEquals,
 
-  #[strum(to_string = "==")]
   Equals,
 
-  #[strum(to_string = ">")]
   GreaterThan,
 
-  #[strum(to_string = ">=")]
   GreaterThanOrEquals,
 
-  #[strum(to_string = "<")]
   LessThan,
 
-  #[strum(to_string = "<=")]
   LessThanOrEquals,
 
-  #[strum(to_string = "{0}")]
-  String(&'token_type str),
+  String(#[cfg_attr(feature = "serde", serde(borrow))] Cow<'token_type, str>),
 
-  #[strum(to_string = "{0}")]
   Number(OrderedFloat<f64>),
 
-  #[strum(to_string = "{0}")]
   Identifier(&'token_type str),
 
-  #[strum(to_string = "{0}")]
-  Keyword(Keyword)
+  // An identifier whose name has been interned, when the lexer is asked to.
+  InternedIdentifier(Symbol),
+
+  Keyword(Keyword),
+
+  // Trivia, which is only emitted when asked for.
+  Whitespace(&'token_type str),
+  LineComment(&'token_type str),
+  BlockComment(&'token_type str),
+
+  Eof
+}
+
+// Written by hand, since strum doesn't let a unit variant display as a lone closing brace.
+impl Display for TokenType<'_> {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      TokenType::OpenParanthesis => formatter.write_str("("),
+      TokenType::CloseParanthesis => formatter.write_str(")"),
+      TokenType::OpenBrace => formatter.write_str("{"),
+      TokenType::CloseBrace => formatter.write_str("}"),
+      TokenType::Comma => formatter.write_str(","),
+      TokenType::Dot => formatter.write_str("."),
+      TokenType::Semicolon => formatter.write_str(";"),
+      TokenType::Plus => formatter.write_str("+"),
+      TokenType::Minus => formatter.write_str("-"),
+      TokenType::Multiply => formatter.write_str("*"),
+      TokenType::Divide => formatter.write_str("/"),
+      TokenType::Percent => formatter.write_str("%"),
+      TokenType::Assign => formatter.write_str("="),
+      TokenType::Not => formatter.write_str("!"),
+      TokenType::NotEquals => formatter.write_str("!="),
+      TokenType::Equals => formatter.write_str("=="),
+      TokenType::GreaterThan => formatter.write_str(">"),
+      TokenType::GreaterThanOrEquals => formatter.write_str(">="),
+      TokenType::LessThan => formatter.write_str("<"),
+      TokenType::LessThanOrEquals => formatter.write_str("<="),
+
+      TokenType::String(string) => write!(formatter, "{string}"),
+      TokenType::Number(number) => write!(formatter, "{number}"),
+      TokenType::Identifier(identifier) => write!(formatter, "{identifier}"),
+      TokenType::InternedIdentifier(symbol) => write!(formatter, "{symbol}"),
+      TokenType::Keyword(keyword) => write!(formatter, "{keyword}"),
+
+      TokenType::Whitespace(lexeme)
+      | TokenType::LineComment(lexeme)
+      | TokenType::BlockComment(lexeme) => formatter.write_str(lexeme),
+
+      TokenType::Eof => formatter.write_str("EOF")
+    }
+  }
+}
+
+impl<'token_type> TokenType<'token_type> {
+  // Returns the value a literal token evaluates to, or None if the token isn't a literal.
+  pub fn to_literal_value(&self) -> Option<Value<'token_type>> {
+    Some(match self {
+      TokenType::Number(number) => Value::Number(*number),
+
+      TokenType::String(string) => Value::String(string.clone()),
+
+      TokenType::Keyword(Keyword::True) => Value::Boolean(true),
+      TokenType::Keyword(Keyword::False) => Value::Boolean(false),
+
+      TokenType::Keyword(Keyword::Nil) => Value::Nil,
+
+      _ => return None
+    })
+  }
 }
 
-#[derive(Debug, PartialEq, Eq, EnumString, Display)]
+// Iterating over the parser's error types fills ErrorType::MisspelledKeyword with the default.
+#[derive(Debug, Default, PartialEq, Eq, EnumString, EnumIter, strum_macros::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[strum(serialize_all = "lowercase")]
 pub enum Keyword {
+  #[default]
   And,
   Class,
+  Div,
   Else,
   False,
   Fun,
   For,
   If,
   Nil,
+  Not,
   Or,
   Print,
   Return,
@@ -113,3 +242,150 @@ pub enum Keyword {
   Var,
   While
 }
+
+impl Keyword {
+  // Returns the keyword the given identifier is a near-miss for (like fnu for fun, or retrun for
+  // return), if any. Used to suggest a fix, when an identifier is found where a keyword was
+  // expected.
+  pub fn suggestion_for(identifier: &str) -> Option<Self> {
+    // Very short identifiers (like i or x) are one typo away from a short keyword (like if or or),
+    // but they're almost always meant as is.
+    if identifier.chars().count() < 3 {
+      return None;
+    }
+
+    Self::iter()
+      .filter_map(|keyword| {
+        let keyword_string = keyword.to_string();
+
+        // The longer the keyword, the more typos we tolerate.
+        let max_distance = (keyword_string.len() / 3).clamp(1, 2);
+
+        let distance = edit_distance(identifier, &keyword_string);
+        ((distance > 0) && (distance <= max_distance)).then_some((distance, keyword))
+      })
+      .min_by_key(|(distance, _)| *distance)
+      .map(|(_, keyword)| keyword)
+  }
+}
+
+// Optimal string alignment distance : the number of insertions, deletions, substitutions and
+// transpositions of adjacent characters, needed to turn one string into the other.
+fn edit_distance(from: &str, to: &str) -> usize {
+  let (from, to): (Vec<_>, Vec<_>) = (from.chars().collect(), to.chars().collect());
+
+  // distances[i][j] is the distance between the first i characters of from and the first j
+  // characters of to.
+  let mut distances = vec![vec![0; to.len() + 1]; from.len() + 1];
+
+  for (i, row) in distances.iter_mut().enumerate() {
+    row[0] = i;
+  }
+  for (j, distance) in distances[0].iter_mut().enumerate() {
+    *distance = j;
+  }
+
+  for i in 1..=from.len() {
+    for j in 1..=to.len() {
+      let substitution_cost = usize::from(from[i - 1] != to[j - 1]);
+
+      distances[i][j] = (distances[i - 1][j] + 1)
+        .min(distances[i][j - 1] + 1)
+        .min(distances[i - 1][j - 1] + substitution_cost);
+
+      if (i > 1) && (j > 1) && (from[i - 1] == to[j - 2]) && (from[i - 2] == to[j - 1]) {
+        distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+      }
+    }
+  }
+
+  distances[from.len()][to.len()]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn brace_display() {
+    assert_eq!(TokenType::OpenBrace.to_string(), "{");
+    assert_eq!(TokenType::CloseBrace.to_string(), "}");
+  }
+
+  #[test]
+  fn literal_values() {
+    assert!(matches!(
+      TokenType::Number(1.5.into()).to_literal_value(),
+      Some(Value::Number(number)) if number == 1.5
+    ));
+    assert!(matches!(
+      TokenType::String("lox".into()).to_literal_value(),
+      Some(Value::String(string)) if string == "lox"
+    ));
+    assert!(matches!(
+      TokenType::Keyword(Keyword::True).to_literal_value(),
+      Some(Value::Boolean(true))
+    ));
+    assert!(matches!(
+      TokenType::Keyword(Keyword::False).to_literal_value(),
+      Some(Value::Boolean(false))
+    ));
+    assert!(matches!(
+      TokenType::Keyword(Keyword::Nil).to_literal_value(),
+      Some(Value::Nil)
+    ));
+  }
+
+  #[test]
+  fn non_literal_values() {
+    assert!(TokenType::Plus.to_literal_value().is_none());
+    assert!(TokenType::Identifier("x").to_literal_value().is_none());
+    assert!(
+      TokenType::Keyword(Keyword::Print)
+        .to_literal_value()
+        .is_none()
+    );
+  }
+
+  #[test]
+  fn keyword_suggestions() {
+    assert_eq!(Keyword::suggestion_for("fnu"), Some(Keyword::Fun));
+    assert_eq!(Keyword::suggestion_for("retrun"), Some(Keyword::Return));
+    assert_eq!(Keyword::suggestion_for("whiel"), Some(Keyword::While));
+    assert_eq!(Keyword::suggestion_for("prnt"), Some(Keyword::Print));
+    assert_eq!(Keyword::suggestion_for("iff"), Some(Keyword::If));
+  }
+
+  #[test]
+  fn no_keyword_suggestions() {
+    // Far off identifiers.
+    assert_eq!(Keyword::suggestion_for("counter"), None);
+    assert_eq!(Keyword::suggestion_for("message"), None);
+
+    // An exact match isn't a typo.
+    assert_eq!(Keyword::suggestion_for("fun"), None);
+
+    // Too short to tell apart from an intended identifier.
+    assert_eq!(Keyword::suggestion_for("i"), None);
+    assert_eq!(Keyword::suggestion_for("o"), None);
+    assert_eq!(Keyword::suggestion_for("x"), None);
+    assert_eq!(Keyword::suggestion_for("fi"), None);
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn serde_round_trip() {
+    use crate::lexer::Lexer;
+
+    let source =
+      "// Prints a greeting.\nprint (1.5 + x) >= 2 and \"say \\\"hi\\\"\" != nil;\n{ y = !true; }";
+    let tokens = Lexer::new(source).lex().unwrap();
+
+    let json = serde_json::to_string(&tokens).unwrap();
+    let deserialized: Vec<Token> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, tokens);
+
+    // Numbers are plain f64s.
+    assert!(json.contains(r#"{"Number":1.5}"#));
+  }
+}
@@ -0,0 +1,5 @@
+pub mod ast;
+pub mod diagnostics;
+pub mod lexer;
+pub mod lsp;
+pub mod render;
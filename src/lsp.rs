@@ -0,0 +1,213 @@
+/*
+  The bridge between this crate and an editor extension : diagnostics shaped like the ones in the
+  Language Server Protocol.
+
+  LSP positions are zero-based, and count the characters of a line in UTF-16 code units.
+*/
+
+use {
+  crate::{
+    ast::parser::Parser,
+    lexer::{
+      Lexer,
+      source::{LineIndex, Span}
+    }
+  },
+  getset::Getters
+};
+
+#[derive(Debug, PartialEq, Eq, Getters)]
+pub struct LspDiagnostic {
+  #[getset(get = "pub")]
+  range: LspRange,
+
+  #[getset(get = "pub")]
+  severity: LspSeverity,
+
+  #[getset(get = "pub")]
+  message: String
+}
+
+#[derive(Debug, PartialEq, Eq, Getters)]
+pub struct LspRange {
+  #[getset(get = "pub")]
+  start: LspPosition,
+
+  #[getset(get = "pub")]
+  end: LspPosition
+}
+
+#[derive(Debug, PartialEq, Eq, Getters)]
+pub struct LspPosition {
+  #[getset(get = "pub")]
+  line: usize,
+
+  #[getset(get = "pub")]
+  character: usize
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LspSeverity {
+  Error = 1,
+  Warning = 2,
+  Information = 3,
+  Hint = 4
+}
+
+// Lexes and parses the source as a program, returning all the diagnostics encountered along the
+// way.
+pub fn diagnostics(source: &str) -> Vec<LspDiagnostic> {
+  let mut diagnostics = Vec::new();
+
+  let line_index = LineIndex::new(source);
+
+  let mut tokens = Vec::new();
+  for result in Lexer::new(source) {
+    match result {
+      Ok(token) => tokens.push(token),
+
+      // The error spans the erroneous part of the source. For e.g., the whole of an unterminated
+      // string.
+      Err(error) => diagnostics.push(LspDiagnostic {
+        range:    LspRange::new(source, &line_index, error.span()),
+        severity: LspSeverity::Error,
+        message:  error.r#type().to_string()
+      })
+    }
+  }
+
+  // Parsing an incomplete token stream would only produce cascaded errors. And there's nothing to
+  // parse, if there's only the EOF token.
+  if !diagnostics.is_empty() || (tokens.len() == 1) {
+    return diagnostics;
+  }
+
+  // The error spans the token it points at.
+  if let Err(error) = Parser::new(tokens).parse_program() {
+    diagnostics.push(LspDiagnostic {
+      range:    LspRange::new(source, &line_index, error.span()),
+      severity: LspSeverity::Error,
+      message:  error.r#type().to_string()
+    });
+  }
+
+  diagnostics
+}
+
+impl LspRange {
+  fn new(source: &str, line_index: &LineIndex, span: &Span) -> Self {
+    Self {
+      start: LspPosition::new(source, line_index, *span.start()),
+      end:   LspPosition::new(source, line_index, *span.end())
+    }
+  }
+}
+
+impl LspPosition {
+  // The line index splits lines the same way the lexer does, so the range lies on the line the
+  // lexer reported.
+  fn new(source: &str, line_index: &LineIndex, offset: u32) -> Self {
+    let position = line_index.position(offset);
+
+    Self {
+      line:      position.line() - 1,
+      character: position.utf16_column(source)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn unterminated_string_range() {
+    let source = "1 +\n  \"🎉 never\nends";
+
+    let diagnostics = diagnostics(source);
+    assert_eq!(diagnostics.len(), 1);
+
+    let diagnostic = &diagnostics[0];
+    assert_eq!(*diagnostic.severity(), LspSeverity::Error);
+    assert_eq!(diagnostic.message(), "unterminated string");
+
+    // From the opening quote till the end of the source.
+    assert_eq!(
+      *diagnostic.range(),
+      LspRange {
+        start: LspPosition {
+          line:      1,
+          character: 2
+        },
+        end:   LspPosition {
+          line:      2,
+          character: 4
+        }
+      }
+    );
+  }
+
+  #[test]
+  fn multiple_lexer_diagnostics() {
+    let diagnostics = diagnostics("1 ^ 2 1٣");
+
+    let messages = diagnostics
+      .iter()
+      .map(|diagnostic| diagnostic.message().as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      messages,
+      ["invalid character", "non-ASCII digit in number literal"]
+    );
+
+    assert_eq!(*diagnostics[1].range().start().character(), 7);
+    assert_eq!(*diagnostics[1].range().end().character(), 8);
+  }
+
+  #[test]
+  fn parser_diagnostic_range() {
+    let diagnostics = diagnostics("1 + (2 * 3");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message(), "expected a close paranthesis");
+
+    // Points at the end of the input, where the close paranthesis was expected.
+    let range = diagnostics[0].range();
+    assert_eq!(
+      (*range.start().character(), *range.end().character()),
+      (10, 10)
+    );
+  }
+
+  #[test]
+  fn missing_semicolon() {
+    let diagnostics = diagnostics("print 1");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message(), "expected a semicolon");
+  }
+
+  #[test]
+  fn lone_carriage_return() {
+    let source = "1\r^";
+
+    let diagnostics = diagnostics(source);
+    assert_eq!(diagnostics.len(), 1);
+
+    // On the line the lexer reports the error at.
+    let error = Lexer::new(source).lex().unwrap_err().remove(0);
+    assert_eq!(error.position().to_line_col(), (2, 1));
+
+    let range = diagnostics[0].range();
+    assert_eq!((*range.start().line(), *range.start().character()), (1, 0));
+    assert_eq!((*range.end().line(), *range.end().character()), (1, 1));
+  }
+
+  #[test]
+  fn no_diagnostics() {
+    assert!(diagnostics("").is_empty());
+    assert!(diagnostics("1 + 2 >= 3;").is_empty());
+    assert!(diagnostics("print 1;").is_empty());
+    assert!(diagnostics("print 1;\n2 * 3;").is_empty());
+  }
+}
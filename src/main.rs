@@ -1,4 +1,128 @@
-mod ast;
-mod lexer;
+use {
+  crafting_interpreters::{
+    ast::{Expression, evaluator::Evaluator, operator::Precedance, parser::Parser},
+    lexer::Lexer
+  },
+  std::{env, process::ExitCode}
+};
 
-fn main() {}
+const USAGE: &str = "usage: crafting-interpreters eval \"<expression>\"
+       crafting-interpreters --parse-only --stats \"<expression>\"
+       crafting-interpreters --operators";
+
+fn main() -> ExitCode {
+  let arguments = env::args().skip(1).collect::<Vec<_>>();
+
+  match arguments
+    .iter()
+    .map(String::as_str)
+    .collect::<Vec<_>>()
+    .as_slice()
+  {
+    ["eval", source] => eval(source),
+
+    ["--parse-only", "--stats", source] => print_stats(source),
+
+    ["--operators"] => {
+      print_operators();
+      ExitCode::SUCCESS
+    }
+
+    _ => {
+      eprintln!("{USAGE}");
+      ExitCode::FAILURE
+    }
+  }
+}
+
+// Lexes, parses and evaluates the given expression, printing the result.
+fn eval(source: &str) -> ExitCode {
+  let Some(expression) = parse(source)
+  else {
+    return ExitCode::FAILURE;
+  };
+
+  match Evaluator::evaluate(*expression) {
+    Ok(value) => {
+      println!("{value}");
+      ExitCode::SUCCESS
+    }
+
+    Err(error) => {
+      eprint!("{}", error.render(source));
+      ExitCode::FAILURE
+    }
+  }
+}
+
+// Parses the given expression, without evaluating it, and prints the number of AST nodes of each
+// kind.
+fn print_stats(source: &str) -> ExitCode {
+  let Some(expression) = parse(source)
+  else {
+    return ExitCode::FAILURE;
+  };
+
+  let node_counts = expression.node_counts();
+
+  println!("{:<20}{}", "literals", node_counts.literals());
+  println!(
+    "{:<20}{}",
+    "unary expressions",
+    node_counts.unary_expressions()
+  );
+  println!(
+    "{:<20}{}",
+    "binary expressions",
+    node_counts.binary_expressions()
+  );
+
+  ExitCode::SUCCESS
+}
+
+// Lexes and parses the given expression, reporting the errors (if any).
+fn parse(source: &str) -> Option<Box<Expression<'_>>> {
+  let tokens = match Lexer::new(source).lex() {
+    Ok(tokens) => tokens,
+
+    Err(errors) => {
+      for error in errors {
+        eprint!("{}", error.render(source));
+      }
+      return None;
+    }
+  };
+
+  // Only the EOF token.
+  if tokens.len() == 1 {
+    eprintln!("error: nothing to evaluate");
+    return None;
+  }
+
+  match Parser::new(tokens).parse() {
+    Ok(expression) => Some(expression),
+
+    Err(error) => {
+      eprint!("{}", error.render(source));
+      None
+    }
+  }
+}
+
+// Prints the operator precedance table.
+fn print_operators() {
+  println!(
+    "{:<10}{:<16}{:<8}associativity",
+    "operator", "precedance", "level"
+  );
+
+  for operator in Precedance::table() {
+    println!(
+      "{:<10}{:<16}{:<8}{}",
+      operator.symbol(),
+      operator.precedance().to_lowercase(),
+      operator.level(),
+      operator.associativity()
+    );
+  }
+}
@@ -0,0 +1,239 @@
+/*
+  Renders Lox source code and ASTs as HTML, for the documentation pages about the language.
+
+  The output is deterministic and self-contained : there's no inline JS or CSS, so the pages are
+  free to style these CSS classes :
+
+    (1) lox-source      : the <pre> element wrapping the highlighted source.
+    (2) tok-keyword     : keywords, like var and print.
+    (3) tok-identifier  : identifiers.
+    (4) tok-number      : number literals.
+    (5) tok-string      : string literals, including the quotes.
+    (6) tok-operator    : operators, like + and >=.
+    (7) tok-punctuation : parantheses, braces, commas, dots and semicolons.
+    (8) tok-error       : the part of the source where the lexer encountered an error.
+
+    (9) lox-ast         : the outermost <ul> element of a rendered AST.
+
+  Whitespaces and comments are copied as is, so the text content of the highlighted source is
+  exactly the original source.
+*/
+
+use {
+  crate::{
+    ast::Expression,
+    lexer::{Lexer, source::Span, token::TokenType}
+  },
+  std::fmt::Write
+};
+
+pub fn highlight_to_html(source: &str) -> String {
+  let mut html = String::from("<pre class=\"lox-source\">");
+
+  let mut lexer = Lexer::new(source);
+
+  // Byte offset till which the source has been rendered.
+  let mut rendered_till = 0;
+
+  while let Some(result) = lexer.next() {
+    let (span, class) = match &result {
+      // Whatever follows the last token gets copied below.
+      Ok(token) if *token.r#type() == TokenType::Eof => break,
+
+      Ok(token) => (*token.span(), token_class(token.r#type())),
+
+      // The erroneous part of the source, spans from where the lexer encountered the error till
+      // where it stopped.
+      Err(error) => (
+        Span::between(error.position(), lexer.position()),
+        "tok-error"
+      )
+    };
+
+    // Whitespaces and comments preceding the token.
+    escape_into(&mut html, &source[rendered_till..(*span.start() as usize)]);
+
+    write!(html, "<span class=\"{class}\">").unwrap();
+    escape_into(&mut html, span.slice(source));
+    html.push_str("</span>");
+
+    rendered_till = *span.end() as usize;
+  }
+
+  escape_into(&mut html, &source[rendered_till..]);
+
+  html.push_str("</pre>");
+  html
+}
+
+// Emits nested <ul> elements, mirroring the tree printed by the Printer.
+pub fn ast_to_html(expression: &Expression) -> String {
+  let mut html = String::from("<ul class=\"lox-ast\">");
+  render_node(&mut html, expression);
+  html.push_str("</ul>");
+
+  html
+}
+
+fn render_node(html: &mut String, expression: &Expression) {
+  // The paranthesis are already implied by the nesting of the list.
+  if let Expression::Grouping(grouping) = expression {
+    return render_node(html, grouping.inner());
+  }
+
+  html.push_str("<li>");
+
+  match expression {
+    Expression::Literal(token) => escape_into(html, &token.r#type().to_string()),
+
+    Expression::UnaryExpression(unary_expression) => {
+      escape_into(html, &unary_expression.operator().precedance().to_string());
+
+      html.push_str("<ul>");
+      render_node(html, unary_expression.operand());
+      html.push_str("</ul>");
+    }
+
+    Expression::BinaryExpression(binary_expression) => {
+      escape_into(html, &binary_expression.operator().precedance().to_string());
+
+      html.push_str("<ul>");
+      render_node(html, binary_expression.left_operand());
+      render_node(html, binary_expression.right_operand());
+      html.push_str("</ul>");
+    }
+
+    Expression::Grouping(_) => unreachable!()
+  }
+
+  html.push_str("</li>");
+}
+
+fn token_class(token_type: &TokenType) -> &'static str {
+  match token_type {
+    TokenType::Keyword(_) => "tok-keyword",
+    TokenType::Identifier(_) | TokenType::InternedIdentifier(_) => "tok-identifier",
+    TokenType::Number(_) => "tok-number",
+    TokenType::String(_) => "tok-string",
+
+    TokenType::OpenParanthesis
+    | TokenType::CloseParanthesis
+    | TokenType::OpenBrace
+    | TokenType::CloseBrace
+    | TokenType::Comma
+    | TokenType::Dot
+    | TokenType::Semicolon => "tok-punctuation",
+
+    TokenType::Plus
+    | TokenType::Minus
+    | TokenType::Multiply
+    | TokenType::Divide
+    | TokenType::Percent
+    | TokenType::Assign
+    | TokenType::Not
+    | TokenType::NotEquals
+    | TokenType::Equals
+    | TokenType::GreaterThan
+    | TokenType::GreaterThanOrEquals
+    | TokenType::LessThan
+    | TokenType::LessThanOrEquals => "tok-operator",
+
+    // The highlighter doesn't lex in the trivia mode.
+    TokenType::Whitespace(_) | TokenType::LineComment(_) | TokenType::BlockComment(_) => {
+      unreachable!("trivia tokens aren't emitted by default")
+    }
+
+    TokenType::Eof => unreachable!("the EOF token isn't rendered")
+  }
+}
+
+fn escape_into(html: &mut String, text: &str) {
+  for character in text.chars() {
+    match character {
+      '&' => html.push_str("&amp;"),
+      '<' => html.push_str("&lt;"),
+      '>' => html.push_str("&gt;"),
+      '"' => html.push_str("&quot;"),
+      '\'' => html.push_str("&#39;"),
+
+      _ => html.push(character)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::{ast::parser::Parser, lexer::Lexer}
+  };
+
+  // Strips the tags and unescapes the entities.
+  fn text_content(html: &str) -> String {
+    let mut text = String::new();
+
+    let mut inside_tag = false;
+    for character in html.chars() {
+      match character {
+        '<' => inside_tag = true,
+        '>' => inside_tag = false,
+
+        _ if !inside_tag => text.push(character),
+        _ => {}
+      }
+    }
+
+    text
+      .replace("&lt;", "<")
+      .replace("&gt;", ">")
+      .replace("&quot;", "\"")
+      .replace("&#39;", "'")
+      .replace("&amp;", "&")
+  }
+
+  #[test]
+  fn highlight_escapes_html() {
+    let source = "print a <= \"x < 'y'\" & 1; // done";
+
+    let html = highlight_to_html(source);
+
+    assert_eq!(
+      html,
+      "<pre class=\"lox-source\">\
+       <span class=\"tok-keyword\">print</span> \
+       <span class=\"tok-identifier\">a</span> \
+       <span class=\"tok-operator\">&lt;=</span> \
+       <span class=\"tok-string\">&quot;x &lt; &#39;y&#39;&quot;</span> \
+       <span class=\"tok-error\">&amp;</span> \
+       <span class=\"tok-number\">1</span>\
+       <span class=\"tok-punctuation\">;</span> \
+       // done\
+       </pre>"
+    );
+  }
+
+  #[test]
+  fn highlight_preserves_source() {
+    let source = "var   café = \"a&b\"; /* <é>\n */\n\tprint (café  <  2) ^ \"unterminated";
+
+    let html = highlight_to_html(source);
+
+    assert_eq!(text_content(&html), source);
+  }
+
+  #[test]
+  fn ast_to_nested_lists() {
+    let source = "-1 < \"<\"";
+
+    let tokens = Lexer::new(source).lex().unwrap();
+    let expression = Parser::new(tokens).parse().unwrap();
+
+    assert_eq!(
+      ast_to_html(&expression),
+      "<ul class=\"lox-ast\"><li>LessThan<ul>\
+       <li>Minus<ul><li>1</li></ul></li>\
+       <li>&lt;</li>\
+       </ul></li></ul>"
+    );
+  }
+}
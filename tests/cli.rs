@@ -0,0 +1,94 @@
+use std::process::{Command, Output};
+
+fn run(arguments: &[&str]) -> Output {
+  Command::new(env!("CARGO_BIN_EXE_crafting-interpreters"))
+    .args(arguments)
+    .output()
+    .unwrap()
+}
+
+#[test]
+fn eval_prints_result() {
+  let output = run(&["eval", "14"]);
+
+  assert!(output.status.success());
+  assert_eq!(String::from_utf8(output.stdout).unwrap(), "14\n");
+}
+
+#[test]
+fn eval_respects_precedance() {
+  let output = run(&["eval", "2+3*4"]);
+
+  assert!(output.status.success());
+  assert_eq!(String::from_utf8(output.stdout).unwrap(), "14\n");
+}
+
+#[test]
+fn eval_reports_errors_against_inline_source() {
+  let output = run(&["eval", "1 + (2 * 3"]);
+
+  assert!(!output.status.success());
+  assert_eq!(
+    String::from_utf8(output.stderr).unwrap(),
+    "error: expected a close paranthesis\n  --> line 1, column 11\n   | 1 + (2 * 3\n   |           ^\n"
+  );
+}
+
+#[test]
+fn eval_rejects_trailing_tokens() {
+  let output = run(&["eval", "1 2"]);
+
+  assert!(!output.status.success());
+  assert!(output.stdout.is_empty());
+  assert_eq!(
+    String::from_utf8(output.stderr).unwrap(),
+    "error: unexpected token\n  --> line 1, column 3\n   | 1 2\n   |   ^\n"
+  );
+}
+
+#[test]
+fn parse_only_stats() {
+  let output = run(&["--parse-only", "--stats", "-(1 + 2) * 3 == !x"]);
+
+  assert!(output.status.success());
+  assert_eq!(
+    String::from_utf8(output.stdout).unwrap(),
+    "literals            4\n\
+     unary expressions   2\n\
+     binary expressions  3\n"
+  );
+}
+
+#[test]
+fn operators_table() {
+  let output = run(&["--operators"]);
+
+  assert!(output.status.success());
+
+  let stdout = String::from_utf8(output.stdout).unwrap();
+  let mut lines = stdout.lines();
+
+  assert_eq!(
+    lines.next(),
+    Some("operator  precedance      level   associativity")
+  );
+  assert_eq!(
+    lines.next(),
+    Some("-         unary           1       right")
+  );
+  assert!(stdout.contains("\n*         multiplicative  2       left\n"));
+  assert!(stdout.contains("\n!=        equality        5       left\n"));
+  assert!(stdout.ends_with("or        or              7       left\n"));
+}
+
+#[test]
+fn usage() {
+  let output = run(&[]);
+
+  assert!(!output.status.success());
+  assert!(
+    String::from_utf8(output.stderr)
+      .unwrap()
+      .starts_with("usage")
+  );
+}